@@ -0,0 +1,392 @@
+//! Commands is the registry of the presenter actions.
+//! Every action has a command, typed at the `:` command line, e.g. `:goto 12` or `:set box false`.
+//! The keys of a presentation are bound to commands in `keys`, and dispatch through the registry as well.
+
+use crate::screen::Screen;
+use crate::NavigationAction;
+
+use std::collections::HashMap;
+
+/// A command of the registry.
+pub struct Command {
+    /// The name the command is typed with.
+    pub name: &'static str,
+    /// The arguments of the command, empty for a command without arguments.
+    pub args: &'static str,
+    /// What the command does.
+    pub help: &'static str,
+}
+
+/// The commands of the registry.
+pub const COMMANDS: &[Command] = &[
+    Command {
+        name: "next",
+        args: "",
+        help: "Move to the next slide",
+    },
+    Command {
+        name: "previous",
+        args: "",
+        help: "Move to the previous slide",
+    },
+    Command {
+        name: "goto",
+        args: "<slide>",
+        help: "Move to a slide by number",
+    },
+    Command {
+        name: "first",
+        args: "",
+        help: "Move to the first slide",
+    },
+    Command {
+        name: "last",
+        args: "",
+        help: "Move to the last slide",
+    },
+    Command {
+        name: "back",
+        args: "",
+        help: "Move back to the slide before the last jump",
+    },
+    Command {
+        name: "next-section",
+        args: "",
+        help: "Move to the first slide of the next section",
+    },
+    Command {
+        name: "previous-section",
+        args: "",
+        help: "Move to the first slide of the previous section",
+    },
+    Command {
+        name: "up",
+        args: "",
+        help: "Scroll up, or move the highlight up",
+    },
+    Command {
+        name: "down",
+        args: "",
+        help: "Scroll down, or move the highlight down",
+    },
+    Command {
+        name: "highlight",
+        args: "",
+        help: "Toggle between highlighting and scrolling",
+    },
+    Command {
+        name: "overview",
+        args: "",
+        help: "Show every slide in a grid",
+    },
+    Command {
+        name: "search",
+        args: "<query>",
+        help: "Search the deck, an empty query ends the search",
+    },
+    Command {
+        name: "next-match",
+        args: "",
+        help: "Move to the next match of the search",
+    },
+    Command {
+        name: "previous-match",
+        args: "",
+        help: "Move to the previous match of the search",
+    },
+    Command {
+        name: "next-link",
+        args: "",
+        help: "Move the focus to the next link of the slide",
+    },
+    Command {
+        name: "previous-link",
+        args: "",
+        help: "Move the focus to the previous link of the slide",
+    },
+    Command {
+        name: "follow",
+        args: "[link]",
+        help: "Follow a link of the slide by number, the focused link by default",
+    },
+    Command {
+        name: "blackout",
+        args: "",
+        help: "Black out the screen until a key is pressed",
+    },
+    Command {
+        name: "whiteout",
+        args: "",
+        help: "White out the screen until a key is pressed",
+    },
+    Command {
+        name: "pause",
+        args: "[minutes]",
+        help: "Show the pause screen, counting down the minutes until the talk goes on",
+    },
+    Command {
+        name: "run",
+        args: "[block]",
+        help: "Run a code block of the slide, the first one by default",
+    },
+    Command {
+        name: "theme",
+        args: "<name>",
+        help: "Present with the style of a theme",
+    },
+    Command {
+        name: "set",
+        args: "<key> <value>",
+        help: "Set a style for the rest of the presentation, e.g. set box false",
+    },
+    Command {
+        name: "reload",
+        args: "",
+        help: "Read the slides and the style again",
+    },
+    Command {
+        name: "notes",
+        args: "",
+        help: "Show or hide the speaker notes of the slides",
+    },
+    Command {
+        name: "export",
+        args: "[file]",
+        help: "Write the deck to a single markdown file",
+    },
+    Command {
+        name: "unlock",
+        args: "",
+        help: "Unlock the keys of a presentation in kiosk mode",
+    },
+    Command {
+        name: "help",
+        args: "",
+        help: "List the commands",
+    },
+    Command {
+        name: "quit",
+        args: "",
+        help: "Exit the presentation",
+    },
+];
+
+/// Parse a command line into the action it stands for.
+/// A command can be shortened to any prefix that names only one command, e.g. `:go 12`.
+/// # Returns
+/// The action, or a message explaining why the command line is not valid.
+pub fn parse(line: &str) -> Result<NavigationAction, String> {
+    let line = line.trim();
+    let (name, args) = match line.split_once(' ') {
+        Some((name, args)) => (name, args.trim()),
+        None => (line, ""),
+    };
+    if name.is_empty() {
+        return Ok(NavigationAction::None);
+    }
+
+    let command = find(name)?;
+    if command.args.starts_with('<') && args.is_empty() && command.name != "search" {
+        return Err(format!("Usage: {} {}", command.name, command.args));
+    }
+
+    let action = match command.name {
+        "next" => NavigationAction::Next,
+        "previous" => NavigationAction::Previous,
+        "goto" => NavigationAction::GoTo(number(command, args)?),
+        "first" => NavigationAction::First,
+        "last" => NavigationAction::Last,
+        "back" => NavigationAction::Back,
+        "next-section" => NavigationAction::NextSection,
+        "previous-section" => NavigationAction::PreviousSection,
+        "up" => NavigationAction::ScrollUp,
+        "down" => NavigationAction::ScrollDown,
+        "highlight" => NavigationAction::ToggleHighlight,
+        "overview" => NavigationAction::Overview,
+        "search" => NavigationAction::Search(args.to_string()),
+        "next-match" => NavigationAction::NextMatch,
+        "previous-match" => NavigationAction::PreviousMatch,
+        "next-link" => NavigationAction::NextLink,
+        "previous-link" => NavigationAction::PreviousLink,
+        "follow" if args.is_empty() => NavigationAction::FollowLink(None),
+        "follow" => NavigationAction::FollowLink(Some(number(command, args)? as usize)),
+        "blackout" => NavigationAction::Blank(Screen::Black),
+        "whiteout" => NavigationAction::Blank(Screen::White),
+        "pause" if args.is_empty() => NavigationAction::Blank(Screen::Pause(None)),
+        "pause" => NavigationAction::Blank(Screen::Pause(Some(number(command, args)?))),
+        "run" if args.is_empty() => NavigationAction::RunCode(1),
+        "run" => NavigationAction::RunCode(number(command, args)? as usize),
+        "theme" => NavigationAction::Theme(args.to_string()),
+        "set" => match args.split_once(' ') {
+            Some((key, value)) => {
+                NavigationAction::Set(key.trim().to_string(), value.trim().to_string())
+            }
+            None => return Err(format!("Usage: {} {}", command.name, command.args)),
+        },
+        "reload" => NavigationAction::Refresh,
+        "notes" => NavigationAction::Notes,
+        "export" if args.is_empty() => NavigationAction::Export(None),
+        "export" => NavigationAction::Export(Some(args.to_string())),
+        "unlock" => NavigationAction::Unlock,
+        "help" => NavigationAction::Message(help()),
+        "quit" => NavigationAction::Exit,
+        _ => NavigationAction::None,
+    };
+
+    Ok(action)
+}
+
+/// Complete a command line.
+/// The names of the commands are completed, along with the style keys of `set` and the themes of `theme`.
+/// # Arguments
+/// * `line` - The command line typed so far.
+/// * `style_map` - The style map, its keys complete `set`.
+/// * `themes` - The names of the themes, they complete `theme`.
+/// # Returns
+/// The command lines the line can be completed to.
+pub fn complete(line: &str, style_map: &HashMap<String, String>, themes: &[String]) -> Vec<String> {
+    let (name, args) = match line.split_once(' ') {
+        Some((name, args)) => (name, args),
+        None => {
+            return COMMANDS
+                .iter()
+                .filter(|command| command.name.starts_with(line))
+                .map(|command| match command.args {
+                    "" => command.name.to_string(),
+                    _ => format!("{} ", command.name),
+                })
+                .collect();
+        }
+    };
+
+    let command = match find(name) {
+        Ok(command) => command,
+        Err(_) => return Vec::new(),
+    };
+    let mut candidates: Vec<String> = match command.name {
+        "set" if !args.contains(' ') => style_map
+            .keys()
+            .filter(|key| key.starts_with(args))
+            .map(|key| format!("set {} ", key))
+            .collect(),
+        "theme" => themes
+            .iter()
+            .filter(|theme| theme.starts_with(args))
+            .map(|theme| format!("theme {}", theme))
+            .collect(),
+        _ => Vec::new(),
+    };
+    candidates.sort();
+    candidates
+}
+
+/// The list of the commands, shown by `:help`.
+pub fn help() -> String {
+    COMMANDS
+        .iter()
+        .map(|command| {
+            let usage = format!("{} {}", command.name, command.args);
+            format!("{:<24} {}", usage.trim_end(), command.help)
+        })
+        .collect::<Vec<String>>()
+        .join("\n")
+}
+
+/// Find a command by its name, or by a prefix of its name that names only one command.
+pub fn find(name: &str) -> Result<&'static Command, String> {
+    if let Some(command) = COMMANDS.iter().find(|command| command.name == name) {
+        return Ok(command);
+    }
+    let matches: Vec<&Command> = COMMANDS
+        .iter()
+        .filter(|command| command.name.starts_with(name))
+        .collect();
+    match matches.as_slice() {
+        [command] => Ok(command),
+        [] => Err(format!("Unknown command '{}', see :help", name)),
+        _ => Err(format!(
+            "'{}' could be {}",
+            name,
+            matches
+                .iter()
+                .map(|command| command.name)
+                .collect::<Vec<&str>>()
+                .join(", ")
+        )),
+    }
+}
+
+/// Parse the number argument of a command.
+fn number(command: &Command, args: &str) -> Result<u32, String> {
+    args.parse()
+        .map_err(|_| format!("Usage: {} {}", command.name, command.args))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn finds_a_command_by_a_unique_prefix() {
+        assert_eq!(find("goto").unwrap().name, "goto");
+        assert_eq!(find("go").unwrap().name, "goto");
+        assert_eq!(find("wh").unwrap().name, "whiteout");
+    }
+
+    #[test]
+    fn prefers_the_exact_name_to_a_prefix() {
+        assert_eq!(find("next").unwrap().name, "next");
+    }
+
+    #[test]
+    fn reports_ambiguous_and_unknown_commands() {
+        let ambiguous = find("p").err().unwrap();
+        assert!(ambiguous.contains("previous") && ambiguous.contains("pause"));
+        assert_eq!(
+            find("fly").err().unwrap(),
+            "Unknown command 'fly', see :help"
+        );
+    }
+
+    #[test]
+    fn parses_commands_and_their_arguments() {
+        assert!(matches!(parse("goto 12"), Ok(NavigationAction::GoTo(12))));
+        assert!(matches!(parse("  go   3 "), Ok(NavigationAction::GoTo(3))));
+        assert!(matches!(parse("run"), Ok(NavigationAction::RunCode(1))));
+        assert!(matches!(parse("run 2"), Ok(NavigationAction::RunCode(2))));
+        assert!(matches!(
+            parse("pause 10"),
+            Ok(NavigationAction::Blank(Screen::Pause(Some(10))))
+        ));
+        assert!(matches!(
+            parse("set box false"),
+            Ok(NavigationAction::Set(key, value)) if key == "box" && value == "false"
+        ));
+        assert!(matches!(parse(""), Ok(NavigationAction::None)));
+    }
+
+    #[test]
+    fn reports_missing_and_invalid_arguments() {
+        assert_eq!(parse("goto").err().unwrap(), "Usage: goto <slide>");
+        assert!(parse("goto twelve").is_err());
+        assert!(parse("set box").is_err());
+    }
+
+    #[test]
+    fn completes_names_keys_and_themes() {
+        let style_map = HashMap::from([
+            ("box".to_string(), "true".to_string()),
+            ("border_color".to_string(), "red".to_string()),
+        ]);
+        let themes = vec!["dark".to_string(), "light".to_string()];
+        assert_eq!(complete("got", &style_map, &themes), vec!["goto "]);
+        assert_eq!(
+            complete("set b", &style_map, &themes),
+            vec!["set border_color ", "set box "]
+        );
+        assert_eq!(
+            complete("theme l", &style_map, &themes),
+            vec!["theme light"]
+        );
+    }
+}
@@ -0,0 +1,365 @@
+//! Deck loads the slides of a project.
+//! A project is either a directory of slides, or a single markdown file split into slides on `---` lines.
+//! The slides of a directory are listed in the `slides` of `deck.yml`. Without a manifest, every
+//! markdown file with a numeric prefix is a slide, in natural order: `1.md`, `2.md`, `03-intro.md`, `10.md`.
+//! Subdirectories with a numeric prefix, like `01-basics/1.md`, are sections named after the directory.
+//! The front matter at the top of a single file deck applies to the whole deck, and the one right after
+//! the `---` that starts a slide applies to that slide.
+
+use crate::include::{expand_includes, is_closing_fence, open_fence};
+use crate::manifest::{self, Settings};
+
+use std::error::Error;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// A slide of the deck.
+pub struct Slide {
+    /// The file the slide is written in.
+    pub path: PathBuf,
+    /// The front matter of the slide.
+    pub front_matter: Settings,
+    /// The markdown of the slide, with the included markdown files expanded.
+    pub markdown: String,
+    /// The section the slide belongs to.
+    pub section: Option<String>,
+}
+
+/// The slides of a project along with the deck wide settings.
+pub struct Deck {
+    /// The project directory, which holds `style.yml` and `deck.yml`.
+    pub dir: PathBuf,
+    /// The settings of `deck.yml`, and of the front matter of a single file deck.
+    pub settings: Settings,
+    pub slides: Vec<Slide>,
+}
+
+/// Load the deck of a project.
+/// # Arguments
+/// * `path` - The project directory, or the markdown file of a single file deck.
+pub fn load(path: &Path) -> Result<Deck, Box<dyn Error>> {
+    if path.is_file() {
+        return load_file(path);
+    }
+
+    let settings = manifest::load(path)?;
+    let mut slides = Vec::new();
+    for (file_path, section) in slide_files(path, &settings)? {
+        let mut slide = read_slide(&file_path)?;
+        slide.section = section;
+        slides.push(slide);
+    }
+
+    Ok(Deck {
+        dir: path.to_path_buf(),
+        settings,
+        slides,
+    })
+}
+
+/// A slide file along with the section it belongs to.
+pub type SlideFile = (PathBuf, Option<String>);
+
+/// List the slide files of a project directory, along with their sections.
+/// The files are taken from the `slides` of the manifest, or discovered by their numeric prefix.
+pub fn slide_files(dir: &Path, settings: &Settings) -> Result<Vec<SlideFile>, Box<dyn Error>> {
+    let entries = match manifest::list(settings, "slides") {
+        Some(entries) => entries,
+        None => return discover(dir, true),
+    };
+
+    // An entry is a slide file or a section directory, or a section title followed by the slide files of the section.
+    let mut files = Vec::new();
+    for entry in entries {
+        let (section, names) = match entry.split_once(": ") {
+            Some((section, names)) => (Some(section.trim().to_string()), names),
+            None => (None, entry.as_str()),
+        };
+        for name in names.split(',').map(|name| name.trim()) {
+            let file_path = dir.join(name);
+            if file_path.is_dir() {
+                let section = section.clone().or(section_name(&file_path));
+                for (file_path, _) in discover(&file_path, false)? {
+                    files.push((file_path, section.clone()));
+                }
+            } else if file_path.is_file() {
+                files.push((file_path, section.clone()));
+            } else {
+                return Err(format!(
+                    "{} lists '{}', which does not exist",
+                    manifest::MANIFEST_FILE,
+                    name
+                )
+                .into());
+            }
+        }
+    }
+
+    Ok(files)
+}
+
+/// Discover the markdown files with a numeric prefix in a directory, in natural order.
+/// Subdirectories with a numeric prefix are sections, their slides are discovered in place.
+fn discover(dir: &Path, sections: bool) -> Result<Vec<SlideFile>, Box<dyn Error>> {
+    let mut entries = Vec::new();
+    for entry in fs::read_dir(dir)? {
+        let file_path = entry?.path();
+        let name = match file_path.file_name().and_then(|name| name.to_str()) {
+            Some(name) => name.to_string(),
+            None => continue,
+        };
+        let slide = file_path.is_file() && name.ends_with(".md");
+        let section = sections && file_path.is_dir();
+        if !slide && !section {
+            continue;
+        }
+        if let Some(number) = numeric_prefix(&name) {
+            entries.push((number, name, file_path));
+        }
+    }
+    entries.sort();
+
+    let mut files = Vec::new();
+    for (_, _, file_path) in entries {
+        if file_path.is_dir() {
+            let section = section_name(&file_path);
+            for (file_path, _) in discover(&file_path, false)? {
+                files.push((file_path, section.clone()));
+            }
+        } else {
+            files.push((file_path, None));
+        }
+    }
+
+    Ok(files)
+}
+
+/// The name of a section directory, without its numeric prefix: `basics` for `01-basics`.
+fn section_name(dir: &Path) -> Option<String> {
+    let name = dir.file_name()?.to_str()?;
+    let name = name
+        .trim_start_matches(|c: char| c.is_ascii_digit())
+        .trim_start_matches(['-', '_', '.', ' '])
+        .replace(['-', '_'], " ");
+    if name.is_empty() {
+        return None;
+    }
+    Some(name)
+}
+
+/// The number a file name starts with, `3` for `03-intro.md`.
+fn numeric_prefix(name: &str) -> Option<u64> {
+    let digits: String = name.chars().take_while(|c| c.is_ascii_digit()).collect();
+    digits.parse().ok()
+}
+
+impl Deck {
+    /// Keep the slides of one section only.
+    /// The section is matched by name, ignoring case, dashes and underscores.
+    pub fn select_section(&mut self, name: &str) -> Result<(), Box<dyn Error>> {
+        let name = name.replace(['-', '_'], " ");
+        self.slides.retain(|slide| {
+            slide
+                .section
+                .as_ref()
+                .is_some_and(|section| section.replace(['-', '_'], " ").eq_ignore_ascii_case(&name))
+        });
+        if self.slides.is_empty() {
+            return Err(format!("Section '{}' not found", name).into());
+        }
+        Ok(())
+    }
+
+    /// The index of the first slide of the section after the given slide.
+    /// Returns `None` if the slide is in the last section.
+    pub fn next_section(&self, index: usize) -> Option<usize> {
+        let section = &self.slides.get(index)?.section;
+        self.slides
+            .iter()
+            .enumerate()
+            .skip(index + 1)
+            .find(|(_, slide)| &slide.section != section)
+            .map(|(i, _)| i)
+    }
+
+    /// The index of the first slide of the section before the given slide.
+    /// Returns `None` if the slide is in the first section.
+    pub fn previous_section(&self, index: usize) -> Option<usize> {
+        let start = self.section_start(index);
+        if start == 0 {
+            return None;
+        }
+        Some(self.section_start(start - 1))
+    }
+
+    /// The index of the first slide of the section of the given slide.
+    fn section_start(&self, index: usize) -> usize {
+        let section = &self.slides[index].section;
+        let mut start = index;
+        while start > 0 && &self.slides[start - 1].section == section {
+            start -= 1;
+        }
+        start
+    }
+}
+
+/// Read a slide written in a file of its own.
+fn read_slide(path: &Path) -> Result<Slide, Box<dyn Error>> {
+    let contents = fs::read_to_string(path)?;
+    let contents = expand_includes(&contents, path)?;
+    let (front_matter, markdown) = manifest::split_front_matter(&contents);
+
+    Ok(Slide {
+        path: path.to_path_buf(),
+        front_matter,
+        markdown,
+        section: None,
+    })
+}
+
+/// Load a single file deck.
+/// The project directory is the directory of the file.
+fn load_file(path: &Path) -> Result<Deck, Box<dyn Error>> {
+    let dir = path.parent().unwrap_or(Path::new(".")).to_path_buf();
+    let contents = fs::read_to_string(path)?;
+    let contents = expand_includes(&contents, path)?;
+    let (front_matter, markdown) = manifest::split_front_matter(&contents);
+
+    let mut settings = manifest::load(&dir)?;
+    settings.extend(front_matter);
+
+    let slides = split_slides(&markdown)
+        .into_iter()
+        .map(|markdown| {
+            let (front_matter, markdown) = split_slide_front_matter(markdown);
+            Slide {
+                path: path.to_path_buf(),
+                front_matter,
+                markdown,
+                section: None,
+            }
+        })
+        .collect();
+
+    Ok(Deck {
+        dir,
+        settings,
+        slides,
+    })
+}
+
+/// Split the front matter off a slide of a single file deck.
+/// The `---` that opens the slide also opens its front matter, which is closed by the next `---`,
+/// e.g. `---`, `tags: [extra]`, `---` between two slides.
+/// # Arguments
+/// * `markdown` - The markdown of the slide, without the `---` that opens it.
+/// # Returns
+/// The settings of the front matter, and the markdown that follows it.
+fn split_slide_front_matter(markdown: String) -> (Settings, String) {
+    let (front_matter, rest) = manifest::split_front_matter(&format!("---\n{}", markdown));
+    if front_matter.is_empty() {
+        (front_matter, markdown)
+    } else {
+        (front_matter, rest)
+    }
+}
+
+/// Split markdown into slides on `---` lines.
+/// A `---` right below a line of text is a heading underline and does not split,
+/// neither does a `---` inside a code fence. Slides without any text are dropped.
+pub fn split_slides(markdown: &str) -> Vec<String> {
+    let mut slides = Vec::new();
+    let mut slide: Vec<&str> = Vec::new();
+    let mut fence: Option<String> = None;
+
+    for line in markdown.lines() {
+        if let Some(open) = &fence {
+            if is_closing_fence(line, open) {
+                fence = None;
+            }
+        } else if let Some((open, _)) = open_fence(line) {
+            fence = Some(open);
+        } else if line.trim_end() == "---" && slide.last().is_none_or(|last| last.trim().is_empty())
+        {
+            slides.push(slide.join("\n"));
+            slide.clear();
+            continue;
+        }
+        slide.push(line);
+    }
+    slides.push(slide.join("\n"));
+
+    slides
+        .into_iter()
+        .filter(|slide| !slide.trim().is_empty())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn splits_slides_on_breaks() {
+        assert_eq!(
+            split_slides("# One\n\n---\n\n# Two\n\n---\n# Three"),
+            vec!["# One\n", "\n# Two\n", "# Three"]
+        );
+    }
+
+    #[test]
+    fn keeps_heading_underlines() {
+        assert_eq!(
+            split_slides("Title\n---\ntext\n\n---\nnext"),
+            vec!["Title\n---\ntext\n", "next"]
+        );
+    }
+
+    #[test]
+    fn keeps_breaks_inside_code_fences() {
+        let markdown = "```yaml\n\n---\nkey: value\n```";
+        assert_eq!(split_slides(markdown), vec![markdown]);
+    }
+
+    #[test]
+    fn drops_empty_slides() {
+        assert_eq!(
+            split_slides("---\n\n---\n# Only\n\n---\n"),
+            vec!["# Only\n"]
+        );
+    }
+
+    #[test]
+    fn splits_the_front_matter_off_a_slide() {
+        let slides = split_slides("# One\n\n---\ntags: [extra]\n---\n\n# Two");
+        let (front_matter, markdown) = split_slide_front_matter(slides[1].clone());
+        assert_eq!(
+            manifest::list(&front_matter, "tags"),
+            Some(&vec!["extra".to_string()])
+        );
+        assert_eq!(markdown, "\n# Two");
+    }
+
+    #[test]
+    fn keeps_a_slide_that_opens_with_a_heading() {
+        let (front_matter, markdown) = split_slide_front_matter("Title\n---\ntext".to_string());
+        assert!(front_matter.is_empty());
+        assert_eq!(markdown, "Title\n---\ntext");
+    }
+
+    #[test]
+    fn reads_numeric_prefixes() {
+        assert_eq!(numeric_prefix("03-intro.md"), Some(3));
+        assert_eq!(numeric_prefix("10.md"), Some(10));
+        assert_eq!(numeric_prefix("intro.md"), None);
+    }
+
+    #[test]
+    fn names_sections_after_their_directory() {
+        assert_eq!(
+            section_name(Path::new("slides/01-getting_started")),
+            Some("getting started".to_string())
+        );
+        assert_eq!(section_name(Path::new("02")), None);
+    }
+}
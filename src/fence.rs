@@ -0,0 +1,193 @@
+//! Fence is a small parser for the info string of a fenced code block.
+//! The info string is everything after the language on the opening fence, `{2-4,7}` in
+//! `` ```rust {2-4,7} ``. Line ranges inside the braces are emphasised and the rest of the
+//! block is dimmed. Ranges separated by `|` are steps, walked through one keypress at a time.
+//! Attributes are written as `key=value` or `key="quoted value"`, flags as a bare word:
+//! `` ```rust {3} title="main.rs" diff ``.
+
+use std::collections::HashMap;
+
+/// The parsed info string of a code fence.
+#[derive(Debug, Default, Clone)]
+pub struct FenceMeta {
+    /// Each step is a list of inclusive, 1-based line ranges to emphasise.
+    /// A fence without line ranges has no steps and is rendered as is.
+    pub steps: Vec<Vec<(usize, usize)>>,
+    /// The `key=value` attributes and bare flags of the fence, flags are stored as `true`.
+    pub attrs: HashMap<String, String>,
+}
+
+impl FenceMeta {
+    /// Parse the info string that follows the language of a code fence.
+    /// Unknown tokens are ignored so that fences written for other tools still render.
+    pub fn parse(meta: Option<&str>) -> FenceMeta {
+        let mut fence = FenceMeta::default();
+        let meta = match meta {
+            Some(meta) => meta,
+            None => return fence,
+        };
+
+        for token in tokenize(meta) {
+            if let Some((key, value)) = token.split_once('=') {
+                fence.attrs.insert(key.to_string(), value.to_string());
+            } else if let Some(steps) = parse_steps(&token) {
+                fence.steps = steps;
+            } else {
+                fence.attrs.insert(token, "true".to_string());
+            }
+        }
+
+        fence
+    }
+
+    /// Get an attribute of the fence.
+    pub fn attr(&self, key: &str) -> Option<&str> {
+        self.attrs.get(key).map(|s| s.as_str())
+    }
+
+    /// Checks whether a bare flag, or an attribute set to `true`, is present on the fence.
+    pub fn flag(&self, key: &str) -> bool {
+        self.attr(key) == Some("true")
+    }
+
+    /// Checks whether a line is emphasised in the given step.
+    /// The last step stays active once the steps are exhausted.
+    /// # Arguments
+    /// * `step` - The current step, starting at 0.
+    /// * `line` - The 1-based line number inside the code block.
+    pub fn is_emphasised(&self, step: usize, line: usize) -> bool {
+        if self.steps.is_empty() {
+            return true;
+        }
+        let ranges = &self.steps[step.min(self.steps.len() - 1)];
+        ranges
+            .iter()
+            .any(|(start, end)| *start <= line && line <= *end)
+    }
+}
+
+/// Splits an info string into tokens on whitespace.
+/// Braces are dropped and double quotes keep whitespace inside a value.
+fn tokenize(meta: &str) -> Vec<String> {
+    let mut tokens = Vec::new();
+    let mut token = String::new();
+    let mut quoted = false;
+
+    for c in meta.chars() {
+        match c {
+            '"' => quoted = !quoted,
+            '{' | '}' if !quoted => {}
+            c if c.is_whitespace() && !quoted => {
+                if !token.is_empty() {
+                    tokens.push(std::mem::take(&mut token));
+                }
+            }
+            c => token.push(c),
+        }
+    }
+    if !token.is_empty() {
+        tokens.push(token);
+    }
+
+    tokens
+}
+
+/// Parses a line range specification such as `1-3|5|7-9` or `2-4,7`.
+/// Returns `None` if the token is not a line range specification.
+fn parse_steps(token: &str) -> Option<Vec<Vec<(usize, usize)>>> {
+    if token.is_empty()
+        || !token
+            .chars()
+            .all(|c| c.is_ascii_digit() || c == ',' || c == '-' || c == '|')
+    {
+        return None;
+    }
+
+    let mut steps = Vec::new();
+    for step in token.split('|') {
+        let mut ranges = Vec::new();
+        for range in step.split(',').filter(|range| !range.is_empty()) {
+            ranges.push(parse_range(range)?);
+        }
+        steps.push(ranges);
+    }
+    Some(steps)
+}
+
+/// Parses a single inclusive range, `7` or `2-4`.
+pub fn parse_range(range: &str) -> Option<(usize, usize)> {
+    match range.split_once('-') {
+        Some((start, end)) => {
+            let start = start.trim().parse::<usize>().ok()?;
+            let end = end.trim().parse::<usize>().ok()?;
+            if start > end {
+                return None;
+            }
+            Some((start, end))
+        }
+        None => {
+            let line = range.trim().parse::<usize>().ok()?;
+            Some((line, line))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_ranges_as_one_step() {
+        assert_eq!(parse_steps("2-4,7"), Some(vec![vec![(2, 4), (7, 7)]]));
+    }
+
+    #[test]
+    fn parses_steps_separated_by_bars() {
+        assert_eq!(
+            parse_steps("1-3|5|7-9"),
+            Some(vec![vec![(1, 3)], vec![(5, 5)], vec![(7, 9)]])
+        );
+    }
+
+    #[test]
+    fn rejects_tokens_that_are_not_ranges() {
+        assert_eq!(parse_steps(""), None);
+        assert_eq!(parse_steps("diff"), None);
+        assert_eq!(parse_steps("4-2"), None);
+        assert_eq!(parse_steps("1--3"), None);
+    }
+
+    #[test]
+    fn parses_a_range() {
+        assert_eq!(parse_range("7"), Some((7, 7)));
+        assert_eq!(parse_range(" 2 - 4 "), Some((2, 4)));
+        assert_eq!(parse_range("a-4"), None);
+    }
+
+    #[test]
+    fn parses_attributes_flags_and_steps() {
+        let fence = FenceMeta::parse(Some(r#"{3|5} title="main file.rs" diff"#));
+        assert_eq!(fence.steps, vec![vec![(3, 3)], vec![(5, 5)]]);
+        assert_eq!(fence.attr("title"), Some("main file.rs"));
+        assert!(fence.flag("diff"));
+        assert!(!fence.flag("numbers"));
+    }
+
+    #[test]
+    fn parses_no_info_string() {
+        let fence = FenceMeta::parse(None);
+        assert!(fence.steps.is_empty());
+        assert!(fence.attrs.is_empty());
+    }
+
+    #[test]
+    fn emphasises_the_lines_of_the_step() {
+        let fence = FenceMeta::parse(Some("{1-2|4}"));
+        assert!(fence.is_emphasised(0, 2));
+        assert!(!fence.is_emphasised(0, 4));
+        assert!(fence.is_emphasised(1, 4));
+        // The last step stays active once the steps are exhausted.
+        assert!(fence.is_emphasised(5, 4));
+        assert!(FenceMeta::parse(None).is_emphasised(0, 9));
+    }
+}
@@ -0,0 +1,345 @@
+//! Include pulls files on disk into the markdown of a slide before it is rendered.
+//! A line with `{{include partials/footer.md}}` is replaced by the markdown of that file,
+//! resolved relative to the file it is written in, so partials can include other partials.
+//! A fence with an `include` attribute has its body replaced by the contents of the file,
+//! resolved relative to the project directory and kept inside it. `lines=10-40` keeps a range
+//! of lines and `region=parse` keeps the lines between the `region: parse` and `endregion: parse`
+//! marker comments.
+//! The expansion runs on the markdown text, so the included code is highlighted and run like any other code.
+
+use crate::fence::{parse_range, FenceMeta};
+
+use std::error::Error;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use lazy_static::lazy_static;
+use regex::Regex;
+
+lazy_static! {
+    /// This is used to match a transclusion directive, which takes up a line of its own
+    static ref INCLUDE_RE: Regex = Regex::new(r"^\s*\{\{\s*include\s+(.+?)\s*\}\}\s*$").unwrap();
+}
+
+/// Expand the `{{include path}}` directives of a markdown file.
+/// Directives inside code fences are left alone. Included files are expanded recursively,
+/// and a file that ends up including itself is reported as a cycle.
+/// # Arguments
+/// * `text` - The markdown text of the file.
+/// * `file` - The path of the file, included paths are relative to its directory.
+/// # Returns
+/// The markdown text with the directives replaced by the included markdown.
+pub fn expand_includes(text: &str, file: &Path) -> Result<String, Box<dyn Error>> {
+    let mut stack = vec![file.canonicalize().unwrap_or(file.to_path_buf())];
+    expand_includes_in(text, file, &mut stack)
+}
+
+/// Expand the directives of a file, with the stack of the files that are being included.
+fn expand_includes_in(
+    text: &str,
+    file: &Path,
+    stack: &mut Vec<PathBuf>,
+) -> Result<String, Box<dyn Error>> {
+    let mut result = Vec::new();
+    let mut fence: Option<String> = None;
+    let dir = file.parent().unwrap_or(Path::new("."));
+
+    for (index, line) in text.lines().enumerate() {
+        if let Some(open) = &fence {
+            if is_closing_fence(line, open) {
+                fence = None;
+            }
+            result.push(line.to_string());
+            continue;
+        }
+        if let Some((open, _)) = open_fence(line) {
+            fence = Some(open);
+            result.push(line.to_string());
+            continue;
+        }
+
+        let captures = match INCLUDE_RE.captures(line) {
+            Some(captures) => captures,
+            None => {
+                result.push(line.to_string());
+                continue;
+            }
+        };
+
+        let path = dir.join(captures[1].trim_matches('"'));
+        let location = format!("{}:{}", file.display(), index + 1);
+        let canonical = path.canonicalize().map_err(|e| {
+            format!(
+                "{}: could not include '{}': {}",
+                location,
+                path.display(),
+                e
+            )
+        })?;
+
+        if stack.contains(&canonical) {
+            let cycle = stack
+                .iter()
+                .chain(std::iter::once(&canonical))
+                .map(|path| path.display().to_string())
+                .collect::<Vec<String>>()
+                .join(" -> ");
+            return Err(format!("{}: include cycle: {}", location, cycle).into());
+        }
+
+        let contents = fs::read_to_string(&path).map_err(|e| {
+            format!(
+                "{}: could not include '{}': {}",
+                location,
+                path.display(),
+                e
+            )
+        })?;
+
+        stack.push(canonical);
+        let expanded = expand_includes_in(&contents, &path, stack)?;
+        stack.pop();
+
+        result.push(expanded);
+    }
+
+    Ok(result.join("\n"))
+}
+
+/// Expand the code fences with an `include` attribute.
+/// # Arguments
+/// * `text` - The markdown text of the slide.
+/// * `base` - The directory the included paths are relative to.
+/// # Returns
+/// The markdown text with the included code in place of the fence bodies.
+pub fn expand_code_includes(text: &str, base: &Path) -> Result<String, Box<dyn Error>> {
+    let mut result = Vec::new();
+    let mut lines = text.lines();
+
+    while let Some(line) = lines.next() {
+        let (fence, info) = match open_fence(line) {
+            Some(open) => open,
+            None => {
+                result.push(line.to_string());
+                continue;
+            }
+        };
+
+        // The whole info string is parsed, a fence without a language has its attributes right after the fence.
+        let meta = FenceMeta::parse(Some(info));
+        let path = match meta.attr("include") {
+            Some(path) => path,
+            None => {
+                // The body of a fence without an include is copied as is, so that fences inside it are not expanded.
+                result.push(line.to_string());
+                for line in lines.by_ref() {
+                    result.push(line.to_string());
+                    if is_closing_fence(line, &fence) {
+                        break;
+                    }
+                }
+                continue;
+            }
+        };
+
+        // The slide text has lost its front matter and may be a part of a single file deck,
+        // so the fence is named by its path rather than by a line number.
+        let (first, code) = read_snippet(base, path, &meta)
+            .map_err(|e| format!("Could not include '{}': {}", path, e))?;
+
+        // The first line number is kept on the fence for the line number gutter.
+        if meta.attr("start").is_some() {
+            result.push(line.to_string());
+        } else {
+            result.push(format!("{} start={}", line, first));
+        }
+        // The included code keeps the indentation of the fence, e.g. inside a list item.
+        let indent = &line[..line.len() - line.trim_start().len()];
+        result.extend(code.lines().map(|line| format!("{}{}", indent, line)));
+
+        // Anything written inside the fence is replaced by the included code.
+        let mut closing = format!("{}{}", indent, fence);
+        for line in lines.by_ref() {
+            if is_closing_fence(line, &fence) {
+                closing = line.to_string();
+                break;
+            }
+        }
+        result.push(closing);
+    }
+
+    Ok(result.join("\n"))
+}
+
+/// Read the part of a file selected by the `lines` or `region` attribute.
+/// Returns the number of the first included line along with the code.
+fn read_snippet(
+    base: &Path,
+    path: &str,
+    meta: &FenceMeta,
+) -> Result<(usize, String), Box<dyn Error>> {
+    let contents = fs::read_to_string(project_path(base, path)?)?;
+    let lines: Vec<&str> = contents.lines().collect();
+
+    if let Some(range) = meta.attr("lines") {
+        let (start, end) = parse_range(range).ok_or(format!("invalid line range '{}'", range))?;
+        if start == 0 || start > lines.len() {
+            return Err(format!(
+                "line {} is out of range, the file has {} lines",
+                start,
+                lines.len()
+            )
+            .into());
+        }
+        let end = end.min(lines.len());
+        return Ok((start, lines[start - 1..end].join("\n")));
+    }
+
+    if let Some(region) = meta.attr("region") {
+        let start = lines
+            .iter()
+            .position(|line| region_marker(line) == Some((false, region)))
+            .ok_or(format!("region '{}' not found", region))?;
+        let end = lines[start..]
+            .iter()
+            .position(|line| region_marker(line) == Some((true, region)))
+            .map(|end| start + end)
+            .ok_or(format!("region '{}' is not closed", region))?;
+
+        // Marker comments of other regions nested in this one are left out as well.
+        let code = lines[start + 1..end]
+            .iter()
+            .filter(|line| region_marker(line).is_none())
+            .copied()
+            .collect::<Vec<&str>>()
+            .join("\n");
+        return Ok((start + 2, code));
+    }
+
+    Ok((1, contents.trim_end_matches('\n').to_string()))
+}
+
+/// Resolve an included path against the project directory.
+/// An absolute path, or one that leads out of the project directory, is an error.
+fn project_path(base: &Path, path: &str) -> Result<PathBuf, Box<dyn Error>> {
+    let dir = base.canonicalize()?;
+    let file = dir.join(path).canonicalize()?;
+    if Path::new(path).is_absolute() || !file.starts_with(&dir) {
+        return Err(format!("'{}' is outside the project directory", path).into());
+    }
+    Ok(file)
+}
+
+/// Checks whether a line is a region marker comment, e.g. `// region: parse` or `# endregion: parse`.
+/// Returns whether it ends the region, and the name of the region.
+fn region_marker(line: &str) -> Option<(bool, &str)> {
+    let comment = line.trim();
+    let comment = ["//", "#", "--", ";", "/*", "<!--"]
+        .iter()
+        .find_map(|start| comment.strip_prefix(start))?;
+    let comment = comment
+        .trim()
+        .trim_end_matches("-->")
+        .trim_end_matches("*/")
+        .trim_end();
+    let (end, name) = match comment.strip_prefix("endregion:") {
+        Some(name) => (true, name),
+        None => (false, comment.strip_prefix("region:")?),
+    };
+    let name = name.trim();
+    if name.is_empty() || name.contains(char::is_whitespace) {
+        return None;
+    }
+    Some((end, name))
+}
+
+/// Checks whether a line opens a code fence.
+/// Returns the fence, e.g. ```` ``` ```` or `~~~`, and the info string after it.
+pub fn open_fence(line: &str) -> Option<(String, &str)> {
+    let trimmed = line.trim_start();
+    let marker = trimmed.chars().next()?;
+    if marker != '`' && marker != '~' {
+        return None;
+    }
+
+    let length = trimmed.chars().take_while(|c| *c == marker).count();
+    if length < 3 {
+        return None;
+    }
+    let fence = &trimmed[..length];
+    let info = trimmed[length..].trim();
+    if marker == '`' && info.contains('`') {
+        return None;
+    }
+
+    Some((fence.to_string(), info))
+}
+
+/// Checks whether a line closes the given code fence.
+pub fn is_closing_fence(line: &str, fence: &str) -> bool {
+    let trimmed = line.trim();
+    let marker = fence.chars().next().unwrap_or('`');
+    trimmed.len() >= fence.len() && trimmed.chars().all(|c| c == marker)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn finds_the_start_and_the_end_of_a_region() {
+        assert_eq!(region_marker("// region: parse"), Some((false, "parse")));
+        assert_eq!(
+            region_marker("    # endregion: parse"),
+            Some((true, "parse"))
+        );
+        assert_eq!(region_marker("-- region:setup"), Some((false, "setup")));
+    }
+
+    #[test]
+    fn finds_regions_in_block_comments() {
+        assert_eq!(region_marker("/* region: parse */"), Some((false, "parse")));
+        assert_eq!(
+            region_marker("<!-- endregion: intro -->"),
+            Some((true, "intro"))
+        );
+    }
+
+    #[test]
+    fn ignores_lines_that_are_not_markers() {
+        assert_eq!(region_marker("let region = 1;"), None);
+        assert_eq!(region_marker("// the region: parse is below"), None);
+        assert_eq!(region_marker("// region:"), None);
+        assert_eq!(region_marker("// region: two words"), None);
+    }
+
+    #[test]
+    fn keeps_included_paths_in_the_project() {
+        let dir = std::env::temp_dir().join(format!("dough-include-{}", std::process::id()));
+        fs::create_dir_all(dir.join("src")).unwrap();
+        fs::write(dir.join("src/lib.rs"), "fn main() {}\n").unwrap();
+        fs::write(dir.join("notes.txt"), "notes\n").unwrap();
+
+        assert!(project_path(&dir, "src/lib.rs").is_ok());
+        assert!(project_path(&dir.join("src"), "../src/lib.rs").is_ok());
+        assert!(project_path(&dir.join("src"), "../notes.txt").is_err());
+        let absolute = dir.join("src/lib.rs").display().to_string();
+        assert!(project_path(&dir, &absolute).is_err());
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn opens_and_closes_fences() {
+        assert_eq!(
+            open_fence("```rust {2}"),
+            Some(("```".to_string(), "rust {2}"))
+        );
+        assert_eq!(open_fence("  ~~~~"), Some(("~~~~".to_string(), "")));
+        assert_eq!(open_fence("``not a fence``"), None);
+        assert_eq!(open_fence("```a`b"), None);
+        assert!(is_closing_fence("````", "```"));
+        assert!(!is_closing_fence("``", "```"));
+        assert!(!is_closing_fence("~~~", "```"));
+    }
+}
@@ -0,0 +1,129 @@
+//! Input reads the keys and the mouse of the terminal in a thread of its own, for as long as dough runs.
+//! Reading the terminal in one place keeps the keys that arrive together, like a count typed quickly
+//! or a key held down, and lets the presentation wait for a key, for a time and for the remote at once.
+//! The commands of the remote come in with the keys, see `send`.
+
+use std::collections::VecDeque;
+use std::io::stdin;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError, Sender};
+use std::sync::{Mutex, Once};
+use std::thread;
+use std::time::Instant;
+
+use lazy_static::lazy_static;
+use termion::event::{Event, Key, MouseEvent};
+use termion::input::TermRead;
+
+/// What ends a wait for input.
+pub enum Wait {
+    Key(Key),
+    Mouse(MouseEvent),
+    /// A command sent from the remote.
+    Command(String),
+    /// The deadline has passed.
+    Timeout,
+    /// The terminal is closed, no more keys will come.
+    Closed,
+}
+
+/// A message of the input thread or of the remote.
+enum Message {
+    Event(Event),
+    Command(String),
+    Closed,
+}
+
+lazy_static! {
+    /// The input of the terminal and of the remote, in the order it came in.
+    static ref CHANNEL: (Mutex<Sender<Message>>, Mutex<Receiver<Message>>) = {
+        let (sender, receiver) = mpsc::channel();
+        (Mutex::new(sender), Mutex::new(receiver))
+    };
+    /// The commands that came in while only a key was waited for, they are handed out by the next `wait`.
+    static ref DEFERRED: Mutex<VecDeque<String>> = Mutex::new(VecDeque::new());
+}
+
+/// This is used to start the input thread once.
+static READER: Once = Once::new();
+
+/// This is set once the terminal is closed.
+static CLOSED: AtomicBool = AtomicBool::new(false);
+
+/// Start reading the terminal, the first time input is waited for.
+fn start() {
+    READER.call_once(|| {
+        let sender = CHANNEL.0.lock().unwrap().clone();
+        thread::spawn(move || {
+            for event in stdin().events() {
+                match event {
+                    Ok(event) => {
+                        let _ = sender.send(Message::Event(event));
+                    }
+                    Err(_) => break,
+                }
+            }
+            CLOSED.store(true, Ordering::SeqCst);
+            let _ = sender.send(Message::Closed);
+        });
+    });
+}
+
+/// Send a command to the presentation, as if it was typed at the command line.
+pub fn send(command: String) {
+    let _ = CHANNEL.0.lock().unwrap().send(Message::Command(command));
+}
+
+/// Receive the next message, until a deadline.
+fn receive(deadline: Option<Instant>) -> Option<Message> {
+    start();
+    let receiver = CHANNEL.1.lock().unwrap();
+    // Once the terminal is closed, the messages left are handed out without waiting.
+    if CLOSED.load(Ordering::SeqCst) {
+        return Some(receiver.try_recv().unwrap_or(Message::Closed));
+    }
+    let received = match deadline {
+        Some(deadline) => receiver.recv_timeout(deadline.saturating_duration_since(Instant::now())),
+        None => receiver.recv().map_err(|_| RecvTimeoutError::Disconnected),
+    };
+    match received {
+        Ok(message) => Some(message),
+        Err(RecvTimeoutError::Timeout) => None,
+        Err(RecvTimeoutError::Disconnected) => Some(Message::Closed),
+    }
+}
+
+/// Wait for a key, the mouse or a command of the remote, until a deadline.
+/// # Arguments
+/// * `deadline` - The time to wait until, `None` to wait for as long as it takes.
+pub fn wait(deadline: Option<Instant>) -> Wait {
+    if let Some(command) = DEFERRED.lock().unwrap().pop_front() {
+        return Wait::Command(command);
+    }
+    loop {
+        match receive(deadline) {
+            Some(Message::Event(Event::Key(key))) => return Wait::Key(key),
+            Some(Message::Event(Event::Mouse(mouse))) => return Wait::Mouse(mouse),
+            Some(Message::Event(Event::Unsupported(_))) => {}
+            Some(Message::Command(command)) => return Wait::Command(command),
+            Some(Message::Closed) => return Wait::Closed,
+            None => return Wait::Timeout,
+        }
+    }
+}
+
+/// Wait for a key, until a deadline, e.g. at a prompt.
+/// The mouse is skipped, and the commands of the remote are kept until the prompt is done.
+/// # Returns
+/// `Wait::Key`, `Wait::Timeout` or `Wait::Closed`.
+pub fn key(deadline: Option<Instant>) -> Wait {
+    loop {
+        match receive(deadline) {
+            Some(Message::Event(Event::Key(key))) => return Wait::Key(key),
+            Some(Message::Event(_)) => {}
+            Some(Message::Command(command)) => DEFERRED.lock().unwrap().push_back(command),
+            Some(Message::Closed) => return Wait::Closed,
+            None => return Wait::Timeout,
+        }
+    }
+}
@@ -0,0 +1,354 @@
+//! Keys binds the keys of a presentation to the commands of the registry.
+//! The bindings are set in the `keys` section of `style.yml`, one command per line with its keys:
+//!
+//! ```yaml
+//! keys:
+//!   next: l, right, space
+//!   up: j
+//!   down: k
+//!   run 2: ctrl+r
+//!   first: g g
+//! ```
+//!
+//! A key is a character, a name like `left`, `pagedown` or `f5`, or a modified key like `ctrl+r`, `alt+x`
+//! or `shift+tab`. Keys separated by spaces are a sequence, pressed one after the other.
+//! A command listed in `keys` loses its default keys, and a key listed in `keys` loses its default command.
+
+use crate::commands;
+use crate::manifest::{self, Settings};
+
+use termion::event::Key;
+
+/// The keys the commands are bound to by default.
+const DEFAULT_KEYS: &[(&str, &str)] = &[
+    ("next", "l, L, right, pagedown"),
+    ("previous", "h, H, left, pageup"),
+    ("up", "k, K, up"),
+    ("down", "j, J, down"),
+    ("quit", "q, Q, esc, ctrl+c"),
+    ("next-section", "]"),
+    ("previous-section", "["),
+    ("first", "g"),
+    ("last", "G"),
+    ("highlight", "t"),
+    ("search", "/"),
+    ("next-match", "n"),
+    ("previous-match", "N"),
+    ("overview", "o, O"),
+    ("reload", "ctrl+r"),
+    ("back", "backspace"),
+    ("follow", "enter"),
+    ("next-link", "tab"),
+    ("previous-link", "shift+tab"),
+    ("run", "r"),
+    // Presentation clickers send PageUp, PageDown, and B or . for a black screen.
+    ("blackout", "b, B, ."),
+    ("whiteout", "w, W"),
+    ("pause", "p, P"),
+    // The unlock sequence is the only key that works in kiosk mode.
+    ("unlock", "ctrl+x ctrl+u"),
+];
+
+/// A key, or a sequence of keys, bound to a command.
+struct Binding {
+    keys: Vec<Key>,
+    /// The keys as they are written in the config, used in messages.
+    spec: String,
+    command: String,
+}
+
+/// The result of looking up the keys pressed so far.
+pub enum Lookup<'a> {
+    /// The keys are bound to a command.
+    Command(&'a str),
+    /// The keys start a sequence, more keys are needed.
+    Pending,
+    /// The keys are not bound.
+    Unbound,
+}
+
+/// The key bindings of a presentation.
+pub struct Keymap {
+    bindings: Vec<Binding>,
+}
+
+impl Keymap {
+    /// Build the key bindings from the `keys` section of the style config, on top of the default keys.
+    /// # Returns
+    /// The key bindings, or a message if a binding is not valid or conflicts with another one.
+    pub fn load(settings: &Settings) -> Result<Keymap, String> {
+        let mut bindings = Vec::new();
+        if let Some(keys) = manifest::map(settings, "keys") {
+            for (command, specs) in keys {
+                // The arguments are checked when the command runs, as a count can be typed before the keys.
+                let name = command.split_whitespace().next().unwrap_or("");
+                commands::find(name).map_err(|e| format!("keys: {}", e))?;
+                bindings.extend(parse_bindings(command, specs)?);
+            }
+        }
+
+        // The defaults are kept for the commands and the keys the config does not mention.
+        for (command, specs) in DEFAULT_KEYS {
+            if manifest::map(settings, "keys").is_some_and(|keys| keys.contains_key(*command)) {
+                continue;
+            }
+            for binding in parse_bindings(command, specs)? {
+                let taken = bindings
+                    .iter()
+                    .any(|other| overlaps(&binding.keys, &other.keys));
+                if !taken {
+                    bindings.push(binding);
+                }
+            }
+        }
+
+        check_conflicts(&bindings)?;
+        Ok(Keymap { bindings })
+    }
+
+    /// Look up the keys pressed so far.
+    pub fn lookup(&self, keys: &[Key]) -> Lookup<'_> {
+        self.find(keys, |_| true)
+    }
+
+    /// Look up the keys pressed so far among the keys of one command, e.g. the unlock sequence in kiosk mode.
+    pub fn lookup_command(&self, keys: &[Key], command: &str) -> Lookup<'_> {
+        self.find(keys, |binding| binding.command == command)
+    }
+
+    /// Look up the keys pressed so far among the bindings a filter keeps.
+    fn find(&self, keys: &[Key], filter: impl Fn(&Binding) -> bool) -> Lookup<'_> {
+        let mut pending = false;
+        for binding in self.bindings.iter().filter(|binding| filter(binding)) {
+            if binding.keys == keys {
+                return Lookup::Command(&binding.command);
+            }
+            if binding.keys.starts_with(keys) {
+                pending = true;
+            }
+        }
+        if pending {
+            Lookup::Pending
+        } else {
+            Lookup::Unbound
+        }
+    }
+}
+
+/// Parse the keys a command is bound to, e.g. `l, right, g g`.
+fn parse_bindings(command: &str, specs: &str) -> Result<Vec<Binding>, String> {
+    let mut bindings = Vec::new();
+    for spec in specs.split(',').map(|spec| spec.trim()) {
+        if spec.is_empty() {
+            continue;
+        }
+        let keys = spec
+            .split_whitespace()
+            .map(parse_key)
+            .collect::<Result<Vec<Key>, String>>()?;
+        // Digits are typed as the count of a command, e.g. `12` and Enter goes to slide 12.
+        if keys
+            .first()
+            .is_some_and(|key| matches!(key, Key::Char(c) if c.is_ascii_digit()))
+        {
+            return Err(format!(
+                "keys: '{}' of '{}' starts with a digit, digits are the count of a command",
+                spec, command
+            ));
+        }
+        bindings.push(Binding {
+            keys,
+            spec: spec.to_string(),
+            command: command.to_string(),
+        });
+    }
+    Ok(bindings)
+}
+
+/// Parse a key, e.g. `q`, `pagedown` or `ctrl+r`.
+fn parse_key(spec: &str) -> Result<Key, String> {
+    let mut chars = spec.chars();
+    if let (Some(c), None) = (chars.next(), chars.next()) {
+        return Ok(Key::Char(c));
+    }
+
+    let lower = spec.to_lowercase();
+    let single = |name: &str| {
+        let mut chars = name.chars();
+        match (chars.next(), chars.next()) {
+            (Some(c), None) => Some(c),
+            _ => None,
+        }
+    };
+    let key = match lower.as_str() {
+        "left" => Key::Left,
+        "right" => Key::Right,
+        "up" => Key::Up,
+        "down" => Key::Down,
+        "home" => Key::Home,
+        "end" => Key::End,
+        "pageup" => Key::PageUp,
+        "pagedown" => Key::PageDown,
+        "tab" => Key::Char('\t'),
+        "shift+tab" => Key::BackTab,
+        "enter" | "return" => Key::Char('\n'),
+        "esc" | "escape" => Key::Esc,
+        "backspace" => Key::Backspace,
+        "delete" => Key::Delete,
+        "insert" => Key::Insert,
+        "space" => Key::Char(' '),
+        "comma" => Key::Char(','),
+        _ => {
+            if let Some(c) = lower.strip_prefix("ctrl+").and_then(single) {
+                Key::Ctrl(c)
+            } else if let Some(c) = spec
+                .strip_prefix("alt+")
+                .or(spec.strip_prefix("Alt+"))
+                .and_then(single)
+            {
+                Key::Alt(c)
+            } else if let Some(n) = lower
+                .strip_prefix('f')
+                .and_then(|n| n.parse::<u8>().ok())
+                .filter(|n| (1..=12).contains(n))
+            {
+                Key::F(n)
+            } else {
+                return Err(format!("keys: unknown key '{}'", spec));
+            }
+        }
+    };
+    Ok(key)
+}
+
+/// Check whether two key sequences are the same, or one starts the other.
+fn overlaps(a: &[Key], b: &[Key]) -> bool {
+    a.starts_with(b) || b.starts_with(a)
+}
+
+/// Check that no key is bound to two commands, and that no key sequence starts another one.
+fn check_conflicts(bindings: &[Binding]) -> Result<(), String> {
+    for (i, a) in bindings.iter().enumerate() {
+        for b in &bindings[i + 1..] {
+            if a.keys == b.keys && a.command != b.command {
+                return Err(format!(
+                    "keys: '{}' is bound to both '{}' and '{}'",
+                    a.spec, a.command, b.command
+                ));
+            }
+            if a.keys != b.keys && overlaps(&a.keys, &b.keys) {
+                let (short, long) = if a.keys.len() < b.keys.len() {
+                    (a, b)
+                } else {
+                    (b, a)
+                };
+                return Err(format!(
+                    "keys: '{}' of '{}' starts '{}' of '{}', so '{}' could never be pressed",
+                    short.spec, short.command, long.spec, long.command, long.spec
+                ));
+            }
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::BTreeMap;
+
+    /// The style settings with the given `keys` section.
+    fn settings(keys: &[(&str, &str)]) -> Settings {
+        let keys: BTreeMap<String, String> = keys
+            .iter()
+            .map(|(command, specs)| (command.to_string(), specs.to_string()))
+            .collect();
+        Settings::from([("keys".to_string(), manifest::Value::Map(keys))])
+    }
+
+    fn bindings(specs: &[(&str, &str)]) -> Vec<Binding> {
+        specs
+            .iter()
+            .flat_map(|(command, specs)| parse_bindings(command, specs).unwrap())
+            .collect()
+    }
+
+    fn command<'a>(keymap: &'a Keymap, keys: &[Key]) -> Option<&'a str> {
+        match keymap.lookup(keys) {
+            Lookup::Command(command) => Some(command),
+            _ => None,
+        }
+    }
+
+    #[test]
+    fn parses_keys() {
+        assert_eq!(parse_key("q"), Ok(Key::Char('q')));
+        assert_eq!(parse_key("PageDown"), Ok(Key::PageDown));
+        assert_eq!(parse_key("ctrl+R"), Ok(Key::Ctrl('r')));
+        assert_eq!(parse_key("alt+X"), Ok(Key::Alt('X')));
+        assert_eq!(parse_key("f5"), Ok(Key::F(5)));
+        assert_eq!(parse_key("space"), Ok(Key::Char(' ')));
+        assert!(parse_key("f13").is_err());
+        assert!(parse_key("hyper+x").is_err());
+    }
+
+    #[test]
+    fn rejects_keys_that_start_with_a_digit() {
+        assert!(parse_bindings("next", "2 x").is_err());
+        assert!(parse_bindings("next", "x 2").is_ok());
+    }
+
+    #[test]
+    fn finds_the_same_keys_bound_twice() {
+        let message = check_conflicts(&bindings(&[("next", "x"), ("previous", "x")]))
+            .err()
+            .unwrap();
+        assert_eq!(message, "keys: 'x' is bound to both 'next' and 'previous'");
+        assert!(check_conflicts(&bindings(&[("next", "x"), ("next", "x")])).is_ok());
+    }
+
+    #[test]
+    fn finds_a_sequence_started_by_another_key() {
+        let message = check_conflicts(&bindings(&[("first", "g g"), ("last", "g")]))
+            .err()
+            .unwrap();
+        assert_eq!(
+            message,
+            "keys: 'g' of 'last' starts 'g g' of 'first', so 'g g' could never be pressed"
+        );
+        assert!(check_conflicts(&bindings(&[("first", "g g"), ("last", "G")])).is_ok());
+    }
+
+    #[test]
+    fn keeps_the_defaults_the_config_does_not_mention() {
+        let keymap = Keymap::load(&settings(&[("first", "g g"), ("up", "x")])).unwrap();
+        assert_eq!(
+            command(&keymap, &[Key::Char('g'), Key::Char('g')]),
+            Some("first")
+        );
+        assert!(matches!(keymap.lookup(&[Key::Char('g')]), Lookup::Pending));
+        assert_eq!(command(&keymap, &[Key::Char('x')]), Some("up"));
+        // The command listed loses its default keys, the others keep them.
+        assert_eq!(command(&keymap, &[Key::Char('k')]), None);
+        assert_eq!(command(&keymap, &[Key::Char('l')]), Some("next"));
+    }
+
+    #[test]
+    fn rejects_unknown_commands() {
+        assert!(Keymap::load(&settings(&[("fly", "x")])).is_err());
+    }
+
+    #[test]
+    fn looks_up_the_keys_of_one_command() {
+        let keymap = Keymap::load(&Settings::new()).unwrap();
+        let unlock = [Key::Ctrl('x'), Key::Ctrl('u')];
+        assert!(matches!(
+            keymap.lookup_command(&unlock, "unlock"),
+            Lookup::Command("unlock")
+        ));
+        assert!(matches!(
+            keymap.lookup_command(&[Key::Char('l')], "unlock"),
+            Lookup::Unbound
+        ));
+    }
+}
@@ -1,22 +1,59 @@
+mod commands;
+mod deck;
+mod fence;
+mod include;
+mod input;
+mod keys;
+mod links;
+mod manifest;
+mod mouse;
+mod overview;
+mod presentation;
 mod prettify;
+mod rehearsal;
+mod remote;
+mod resume;
+mod screen;
+mod search;
+mod share;
+mod subset;
+mod timer;
+mod toc;
 extern crate termion;
 mod ramen;
 mod utils;
+mod vars;
+
+use crate::input::Wait;
+use crate::keys::{Keymap, Lookup};
+use crate::presentation::Presentation;
 use crate::ramen::run_code;
-use crate::utils::{remove_comments, remove_last_n_lines};
+use crate::rehearsal::Rehearsal;
+pub use crate::remote::DEFAULT_ADDRESS as DEFAULT_REMOTE_ADDRESS;
+use crate::remote::{Remote, Status};
+use crate::screen::Screen;
+use crate::search::Search;
+pub use crate::share::DEFAULT_ADDRESS;
+use crate::share::{Share, State};
+use crate::subset::{Filter, Subset};
+pub use crate::timer::parse_duration;
+use crate::timer::{Ticker, Timer};
+use crate::utils::{parse_style, remove_comments, remove_last_n_lines};
 
 use std::error::Error;
 use std::fmt;
 use std::fs;
 use std::thread;
 
+use colored::Colorize;
 use paris::Logger;
 
 use std::collections::HashMap;
-use std::io::{stdin, stdout, Result, Write};
+use std::io::{stdout, Result, Write};
 use std::process::exit;
+use std::time::Duration;
 use termion::event::Key;
-use termion::input::TermRead;
+use termion::input::MouseTerminal;
 use termion::raw::IntoRawMode;
 
 // All the possible navigation actions when presenting a project.
@@ -29,6 +66,88 @@ enum NavigationAction {
     ScrollDown,
     Refresh,
     ToggleHighlight,
+    NextSection,
+    PreviousSection,
+    GoTo(u32),
+    First,
+    Last,
+    Back,
+    Overview,
+    Search(String),
+    NextMatch,
+    PreviousMatch,
+    NextLink,
+    PreviousLink,
+    FollowLink(Option<usize>),
+    Blank(Screen),
+    RunCode(usize),
+    Theme(String),
+    Set(String, String),
+    Notes,
+    Export(Option<String>),
+    Message(String),
+    Unlock,
+}
+
+// The position of a slide in the deck, shown in the footer.
+struct Progress<'a> {
+    // The position of the slide among the slides presented, `None` for a hidden slide.
+    slide: Option<u32>,
+    total: u32,
+    section: Option<&'a str>,
+    title: Option<&'a str>,
+    // The time of the talk, against its plan.
+    timer: &'a Timer,
+    // A message shown on the last line of the terminal, in place of the footer.
+    status: Option<String>,
+}
+
+impl Progress<'_> {
+    /// Fill in the placeholders of the footer template.
+    /// The placeholders are {slide}, {total}, {section} and {title}, the time placeholders are filled in by the timer.
+    /// A hidden slide shows `hidden` in place of its number.
+    fn footer(&self, template: &str) -> String {
+        template
+            .replace(
+                "{slide}",
+                &self
+                    .slide
+                    .map(|slide| slide.to_string())
+                    .unwrap_or("hidden".to_string()),
+            )
+            .replace("{total}", &self.total.to_string())
+            .replace("{section}", self.section.unwrap_or(""))
+            .replace("{title}", self.title.unwrap_or(""))
+    }
+}
+
+// The options of a presentation, set from the command line.
+#[derive(Default)]
+pub struct PresentOptions {
+    // Present the slides of one section only.
+    pub section: Option<String>,
+    // The slide to start the presentation at.
+    pub start: Option<u32>,
+    // Record the time of each slide, and write a report at the end.
+    pub rehearse: bool,
+    // Advance to the next slide after this time, unless the slide sets a `duration` of its own.
+    pub auto: Option<Duration>,
+    // Wrap around to the first slide after the last one, and to the last slide before the first one.
+    pub wrap: bool,
+    // Ignore every key but the unlock sequence.
+    pub kiosk: bool,
+    // Open the presentation where it was last left.
+    pub resume: bool,
+    // Present only the slides with one of these tags, separated by commas.
+    pub only_tags: Option<String>,
+    // Hide the slides with one of these tags, separated by commas.
+    pub skip_tags: Option<String>,
+    // Present only these slides, as ranges of slide numbers, e.g. 3-9,12.
+    pub slides: Option<String>,
+    // Share the presentation on this address, for `dough follow`.
+    pub share: Option<String>,
+    // Serve the remote control on this address.
+    pub remote: Option<String>,
 }
 
 // Define a struct to hold project information.
@@ -124,14 +243,24 @@ impl Project {
             self.fs_path.join("style.yml"),
         )?;
 
+        // Copy the deck manifest, if the template has one.
+        let manifest_path = self.template.join(manifest::MANIFEST_FILE);
+        if manifest_path.exists() {
+            fs::copy(&manifest_path, self.fs_path.join(manifest::MANIFEST_FILE))?;
+        }
+
         // Copy all the slides from the template into the project directory.
-        for i in 1u64.. {
-            let file_path = self.template.join(i.to_string() + ".md");
-            if !file_path.exists() {
-                break;
+        let settings =
+            manifest::load(&self.template).map_err(|e| std::io::Error::other(e.to_string()))?;
+        let files = deck::slide_files(&self.template, &settings)
+            .map_err(|e| std::io::Error::other(e.to_string()))?;
+        for (file_path, _section) in files {
+            let relative_path = file_path.strip_prefix(&self.template).unwrap_or(&file_path);
+            let output_path = self.fs_path.join(relative_path);
+            if let Some(parent) = output_path.parent() {
+                fs::create_dir_all(parent)?;
             }
-            let output_path = self.fs_path.join(i.to_string() + ".md");
-            fs::copy(file_path, output_path)?;
+            fs::copy(&file_path, output_path)?;
         }
 
         return Ok(());
@@ -144,18 +273,24 @@ impl Project {
     /// style_map - The style map used to describe the style of the slides.
     /// render - A boolean indicating whether to render the slide or not.
     /// lines - The number of lines to be rendered.
+    /// * `progress` - The position of the slide in the deck.
     /// # Returns
-    /// A result containing a tuple of the navigation action and the number of lines to be rendered.
+    /// A result containing a tuple of the navigation action, the number of lines to be rendered
+    /// and the time spent at the command line and the search prompts.
 
     fn render_term(
         self: &Self,
         file_contents: &str,
         style_map: &HashMap<String, String>,
-        highlight: bool,
-        render: bool,
-        lines: &mut u32,
-        current_slide: u32,
-    ) -> std::result::Result<(NavigationAction, u32), Box<dyn Error>> {
+        presentation: &mut Presentation,
+        keymap: &Keymap,
+        progress: Progress,
+    ) -> std::result::Result<(NavigationAction, u32, Duration), Box<dyn Error>> {
+        let highlight = presentation.highlight;
+        let render = presentation.render;
+        let mut prompted = Duration::ZERO;
+        let lines = &mut presentation.lines;
+
         // Used to check whether all the lines will be rendered or will it be rendered one by one.
         // Based on the config in the style map
         let clear = if style_map.get("clear").unwrap() == "true" {
@@ -170,9 +305,6 @@ impl Project {
             false
         };
 
-        // The custom Paris logger, used to log the progress of the presentation.
-        let mut log = Logger::new();
-
         let slide;
 
         // The number of lines to be rendered.
@@ -202,7 +334,7 @@ impl Project {
 
             // if clear is true, the slide is cleared after rendering, enabling users to scroll down lines one by one
             if render && clear {
-                return Ok((NavigationAction::ToggleHighlight, line_number));
+                return Ok((NavigationAction::ToggleHighlight, line_number, prompted));
             }
         }
         // The scroll mode
@@ -245,23 +377,37 @@ impl Project {
                 print!("{}", remove_last_n_lines(&slide, line_number));
             }
         }
-        // The progress implies the number of slides that have been rendered/ the total number of slides.
+        // The footer shows the progress of the presentation on the last line of the terminal.
         // It is rendered based on the config in the style map.
-        match style_map.get("progress").unwrap().as_str() {
-            "true" => {
-                print!("\r");
-                log.info(format!(
-                    "[{}/{}]",
-                    current_slide,
-                    fs::read_dir(&self.fs_path)?.count() - 1
-                ));
+        // A footer with the time is drawn again every second, until a key is pressed.
+        let mut ticker = None;
+        if let Some(text) = Self::footer_text(style_map, &progress) {
+            let fraction = progress.slide.unwrap_or(0) as f32 / progress.total.max(1) as f32;
+            print!(
+                "{}",
+                Self::footer(&text, fraction, style_map, progress.timer)
+            );
+            if timer::shows_time(&text) && progress.status.is_none() {
+                let style_map = style_map.clone();
+                let timer = progress.timer.clone();
+                ticker = Some(Ticker::start(move || {
+                    Self::footer(&text, fraction, &style_map, &timer)
+                }));
             }
-            _ => {}
         }
 
         // The stdout is flushed to ensure that the slide is rendered properly.
-        let stdin = stdin();
-        let mut stdout = stdout().into_raw_mode()?;
+        let raw_stdout = stdout().into_raw_mode()?;
+        // The mouse is reported as events along with the keys when it is enabled.
+        let mouse = style_map.get("mouse").map(|s| s.as_str()).unwrap_or("true") == "true";
+        let mut stdout: Box<dyn Write> = if mouse {
+            Box::new(MouseTerminal::from(raw_stdout))
+        } else {
+            Box::new(raw_stdout)
+        };
+        if let Some(status) = &progress.status {
+            Self::show_line(&mut stdout, status)?;
+        }
 
         stdout.flush()?;
 
@@ -275,60 +421,565 @@ impl Project {
         // 6. ToggleHighlight - Toggle between highlighting and scrolling modes.
         // 7. Refresh - Refresh the slide.
         // 8. RunCode - Run the code block in the slide acc to the order of thei appearance.
-        // 9. None - Do nothing.
+        // 9. NextSection - Move to the first slide of the next section.
+        // 10. PreviousSection - Move to the first slide of the previous section.
+        // 11. GoTo - Move to the slide with the number typed before Enter.
+        // 12. First, Last - Move to the first or the last slide.
+        // 13. Back - Move back to the slide before the last jump.
+        // 14. Overview - Show every slide in a grid and jump to the one picked.
+        // 15. Search - Search the deck for the query typed after `/`.
+        // 16. NextMatch, PreviousMatch - Move to the next or the previous match of the search.
+        // 17. NextLink, PreviousLink - Move the focus to the next or the previous link of the slide.
+        // 18. FollowLink - Follow the focused link, or the link clicked, to a slide of the deck or outside of it.
+        // 19. Blank - Show a black, a white or a pause screen until a key is pressed.
+        // 20. Theme, Set - Change the style of the presentation from the command line.
+        // 21. Notes - Show or hide the speaker notes.
+        // 22. Export - Write the deck to a single markdown file.
+        // 23. Message - Show a message, like the error of a command, on the last line.
+        // 24. Unlock - Leave kiosk mode, the keys work again.
+        // 25. None - Do nothing.
+        // A slide that advances by itself moves to the next slide when no key is pressed in time.
+        // The commands sent from the remote are dispatched the same way, in kiosk mode as well.
+        // Every action is a command of the registry in `commands`, the keys and the mouse are bound to commands.
 
         // TODO: Add a watcher here, any changes will call NavigationAction::Refresh
 
-        for c in stdin.keys() {
-            match c? {
-                Key::Right | Key::Char('l') | Key::Char('L') => {
-                    return Ok((NavigationAction::Next, line_number))
-                }
-                Key::Left | Key::Char('h') | Key::Char('H') => {
-                    return Ok((NavigationAction::Previous, line_number));
-                }
-                //add escape and ctrl + c here
-                Key::Char('q') | Key::Char('Q') => {
-                    return Ok((NavigationAction::Exit, line_number))
-                }
-                Key::Esc | Key::Ctrl('c') => return Ok((NavigationAction::Exit, line_number)),
-                Key::Up | Key::Char('k') | Key::Char('K') => {
-                    return Ok((NavigationAction::ScrollUp, line_number))
-                }
-                Key::Down | Key::Char('j') | Key::Char('J') => {
-                    return Ok((NavigationAction::ScrollDown, line_number))
-                }
-                Key::Char('t') => return Ok((NavigationAction::ToggleHighlight, line_number)),
-                Key::Ctrl('r') => return Ok((NavigationAction::Refresh, line_number)),
-                Key::Char(c) if ('0'..='9').contains(&c) => {
-                    let mut log = Logger::new();
-                    let style_map_clone = style_map.clone(); // Clone the style_map for the new thread
-                    let c_num = (c as u8 - '0' as u8) as usize;
-                    thread::Builder::new()
-                        .name("ramen:".to_string())
-                        .spawn(move || {
-                            let output = Self::run_code(c_num, style_map_clone);
-                            match output {
-                                Ok(output) => {
-                                    log.success(format!("\r{}:", c_num));
-                                    output.lines().for_each(|line| println!("\r{}", line));
-                                    print!("\n");
-                                }
-                                Err(e) => {
-                                    log.error(format!("\r{} : {} ", c_num, e.to_string()));
-                                }
+        // Digits typed before a key are a count: `12` and Enter goes to slide 12, `2r` runs the second code block, `10p` pauses for 10 minutes.
+        let mut count = String::new();
+
+        // The keys of a sequence pressed so far, e.g. the first `g` of `g g`.
+        let mut pending: Vec<Key> = Vec::new();
+
+        // The slide advances by itself once its time is up.
+        let deadline = presentation
+            .advance
+            .map(|advance| std::time::Instant::now() + advance);
+
+        loop {
+            // The key is turned into a command line, which dispatches through the command registry.
+            let key = match input::wait(deadline) {
+                Wait::Key(key) => key,
+                Wait::Timeout => return Ok((NavigationAction::Next, line_number, prompted)),
+                // The presentation ends when the terminal is closed.
+                Wait::Closed => return Ok((NavigationAction::Exit, line_number, prompted)),
+                Wait::Command(command) => match commands::parse(&command) {
+                    Ok(NavigationAction::RunCode(c_num)) => {
+                        Self::run_code_in_background(c_num, style_map);
+                        continue;
+                    }
+                    Ok(action) => return Ok((action, line_number, prompted)),
+                    Err(message) => {
+                        return Ok((NavigationAction::Message(message), line_number, prompted))
+                    }
+                },
+                // The mouse is ignored in kiosk mode.
+                Wait::Mouse(_) if presentation.kiosk => continue,
+                Wait::Mouse(mouse_event) => {
+                    let (width, _height) = termion::terminal_size()?;
+                    let links = prettify::get_link_texts();
+                    let codes = prettify::get_code_rows();
+                    match mouse::command(&mouse_event, &slide, &links, &codes, width) {
+                        Some(command) => match commands::parse(&command) {
+                            Ok(NavigationAction::RunCode(c_num)) => {
+                                Self::run_code_in_background(c_num, style_map);
+                                continue;
                             }
-                        })
-                        .expect("Failed to spawn thread");
+                            Ok(action) => return Ok((action, line_number, prompted)),
+                            Err(_) => continue,
+                        },
+                        None => continue,
+                    }
+                }
+            };
+            // In kiosk mode, the keys are only matched against the unlock sequence.
+            if presentation.kiosk {
+                pending.push(key);
+                match keymap.lookup_command(&pending, "unlock") {
+                    Lookup::Command(_) => {
+                        return Ok((NavigationAction::Unlock, line_number, prompted))
+                    }
+                    Lookup::Pending => {}
+                    // A wrong key starts the sequence over, it can be its first key.
+                    Lookup::Unbound => {
+                        pending.clear();
+                        if let Lookup::Pending = keymap.lookup_command(&[key], "unlock") {
+                            pending.push(key);
+                        }
+                    }
+                }
+                continue;
+            }
+            let mut command = match key {
+                // Escape cancels a count or a sequence before it exits the presentation.
+                Key::Esc if !count.is_empty() || !pending.is_empty() => {
+                    return Ok((NavigationAction::None, line_number, prompted));
+                }
+                Key::Char(c) if c.is_ascii_digit() && pending.is_empty() => {
+                    // The count is shown on the last line of the terminal while it is typed, in place of the footer.
+                    ticker.take();
+                    count.push(c);
+                    Self::show_line(&mut stdout, &count)?;
                     continue;
                 }
+                Key::Backspace if !count.is_empty() => {
+                    count.pop();
+                    Self::show_line(&mut stdout, &count)?;
+                    continue;
+                }
+                Key::Char('\n') if !count.is_empty() => format!("goto {}", count),
+                Key::Char(':') if pending.is_empty() => {
+                    ticker.take();
+                    let themes = self.themes();
+                    let complete = |line: &str| commands::complete(line, style_map, &themes);
+                    let asked = std::time::Instant::now();
+                    let line = Self::prompt(&mut stdout, ":", &complete)?;
+                    prompted += asked.elapsed();
+                    match line {
+                        Some(line) => line,
+                        None => return Ok((NavigationAction::None, line_number, prompted)),
+                    }
+                }
+                key => {
+                    pending.push(key);
+                    match keymap.lookup(&pending) {
+                        Lookup::Command(command) => {
+                            pending.clear();
+                            command.to_string()
+                        }
+                        Lookup::Pending => continue,
+                        Lookup::Unbound => {
+                            pending.clear();
+                            continue;
+                        }
+                    }
+                }
+            };
+
+            // A search from a key asks for the query.
+            if command == "search" {
+                ticker.take();
+                let asked = std::time::Instant::now();
+                let query = Self::prompt(&mut stdout, "/", &|_| Vec::new())?;
+                prompted += asked.elapsed();
+                command = match query {
+                    Some(query) => format!("search {}", query),
+                    None => return Ok((NavigationAction::None, line_number, prompted)),
+                };
+            }
+            // The count is the argument of a command bound to a key, e.g. `10p` pauses for 10 minutes.
+            if !count.is_empty() && !command.contains(' ') {
+                command = format!("{} {}", command, count);
+            }
+            count.clear();
+
+            match commands::parse(&command) {
+                // The code runs in the background, the slide is not rendered again.
+                Ok(NavigationAction::RunCode(c_num)) => {
+                    Self::run_code_in_background(c_num, style_map);
+                    continue;
+                }
+                Ok(action) => return Ok((action, line_number, prompted)),
+                Err(message) => {
+                    return Ok((NavigationAction::Message(message), line_number, prompted))
+                }
+            }
+        }
+    }
+
+    /// The text of the footer, with the position of the slide filled in and the time placeholders left.
+    fn footer_text(style_map: &HashMap<String, String>, progress: &Progress) -> Option<String> {
+        prettify::footer_template(style_map).map(|template| progress.footer(&template))
+    }
+
+    /// A talk with a duration shows its time in the footer, unless the footer is set in the style config.
+    /// The footer is set in the style map, so that the slide leaves the last line of the terminal to it.
+    fn plan_footer(style_map: &mut HashMap<String, String>, timer: &Timer) {
+        let footer_set = style_map
+            .get("footer")
+            .is_some_and(|footer| !footer.trim_matches(|c| c == '"' || c == '\'').is_empty());
+        if timer.is_planned() && !footer_set {
+            let template = prettify::footer_template(style_map).unwrap_or_default();
+            style_map.insert(
+                "footer".to_string(),
+                format!("{}  {{elapsed}} / {{remaining}}", template),
+            );
+        }
+    }
+
+    /// Render the footer, with the time of the talk filled in.
+    /// The footer takes the color of the pace of the talk when it falls behind the plan.
+    fn footer(
+        text: &str,
+        fraction: f32,
+        style_map: &HashMap<String, String>,
+        timer: &Timer,
+    ) -> String {
+        let text = timer.fill(text);
+        match timer.color(style_map) {
+            Some(color) => {
+                let mut style_map = style_map.clone();
+                style_map.insert("footer_color".to_string(), color.to_string());
+                prettify::render_footer(&text, fraction, &style_map)
+            }
+            None => prettify::render_footer(&text, fraction, style_map),
+        }
+    }
+
+    /// Show text, like the count being typed, on the last lines of the terminal.
+    /// Text with several lines ends on the last line of the terminal.
+    fn show_line(stdout: &mut impl Write, text: &str) -> Result<()> {
+        let (_width, height) = termion::terminal_size()?;
+        let lines: Vec<&str> = text.split('\n').collect();
+        let first = (height as usize + 1).saturating_sub(lines.len()).max(1);
+        for (i, line) in lines.iter().enumerate() {
+            write!(
+                stdout,
+                "{}{}{}",
+                termion::cursor::Goto(1, (first + i) as u16),
+                termion::clear::CurrentLine,
+                line
+            )?;
+        }
+        stdout.flush()?;
+        Ok(())
+    }
+
+    /// Read a line of input at a prompt on the last line of the terminal.
+    /// # Arguments
+    /// * `prefix` - The text shown before the input, e.g. `/` for a search.
+    /// * `complete` - Lists the completions of the input, Tab completes the input as far as they agree.
+    /// # Returns
+    /// The input once Enter is pressed, or `None` if the prompt is cancelled with Escape,
+    /// or with Backspace on an empty input.
+    fn prompt(
+        stdout: &mut impl Write,
+        prefix: &str,
+        complete: &dyn Fn(&str) -> Vec<String>,
+    ) -> Result<Option<String>> {
+        let mut input = String::new();
+        Self::show_line(stdout, prefix)?;
+        while let Wait::Key(key) = input::key(None) {
+            // The completions are listed after the input when they do not agree.
+            let mut hint = String::new();
+            match key {
+                Key::Char('\n') => return Ok(Some(input)),
+                Key::Esc | Key::Ctrl('c') => return Ok(None),
+                Key::Backspace if input.is_empty() => return Ok(None),
+                Key::Backspace => {
+                    input.pop();
+                }
+                Key::Char('\t') => {
+                    let candidates = complete(&input);
+                    if let Some(first) = candidates.first() {
+                        input = candidates.iter().fold(first.clone(), |common, candidate| {
+                            common
+                                .chars()
+                                .zip(candidate.chars())
+                                .take_while(|(a, b)| a == b)
+                                .map(|(a, _)| a)
+                                .collect()
+                        });
+                    }
+                    if candidates.len() > 1 {
+                        hint = format!("  {}", candidates.join("  ")).dimmed().to_string();
+                    }
+                }
+                Key::Char(c) => input.push(c),
                 _ => continue,
             }
+            Self::show_line(stdout, &format!("{}{}{}", prefix, input, hint))?;
+        }
+        Ok(None)
+    }
+
+    /// Run a code block of the slide in a thread of its own, the output is printed below the slide.
+    fn run_code_in_background(c_num: usize, style_map: &HashMap<String, String>) {
+        let mut log = Logger::new();
+        let style_map_clone = style_map.clone(); // Clone the style_map for the new thread
+        thread::Builder::new()
+            .name("ramen:".to_string())
+            .spawn(move || {
+                let output = Self::run_code(c_num, style_map_clone);
+                match output {
+                    Ok(output) => {
+                        log.success(format!("\r{}:", c_num));
+                        output.lines().for_each(|line| println!("\r{}", line));
+                        print!("\n");
+                    }
+                    Err(e) => {
+                        log.error(format!("\r{} : {} ", c_num, e.to_string()));
+                    }
+                }
+            })
+            .expect("Failed to spawn thread");
+    }
+
+    /// The variables of a slide, without the ones of its front matter.
+    /// These are substituted in the style config as well.
+    /// `slide` and `total` count the slides presented, a hidden slide is numbered in the whole deck.
+    fn slide_variables(
+        deck: &deck::Deck,
+        subset: &Subset,
+        index: usize,
+    ) -> HashMap<String, String> {
+        let number = index as u32 + 1;
+        let mut variables = match subset.position(number) {
+            Some(position) => vars::builtins(position, subset.len()),
+            None => vars::builtins(number, deck.slides.len() as u32),
+        };
+        vars::extend(&mut variables, &deck.settings);
+        if let Some(section) = &deck.slides[index].section {
+            variables.insert("section".to_string(), section.clone());
+        }
+        variables
+    }
+
+    /// Plan a talk with the `duration` of the deck and the `time` of each slide.
+    fn plan(
+        deck: &deck::Deck,
+        subset: &Subset,
+        timer: &mut Timer,
+    ) -> std::result::Result<(), Box<dyn Error>> {
+        let parse = |value: Option<&str>, what: &str| match value {
+            Some(value) => timer::parse_duration(value).map(Some).ok_or_else(|| {
+                DoughError(format!(
+                    "The {} '{}' is not a duration, e.g. 20m, 90s or 1h30m",
+                    what, value
+                ))
+            }),
+            None => Ok(None),
+        };
+        let duration = parse(manifest::scalar(&deck.settings, "duration"), "duration")?;
+        // The hidden slides take no time, the talk is shared among the slides presented.
+        let times = deck
+            .slides
+            .iter()
+            .enumerate()
+            .map(|(index, slide)| {
+                let time = parse(manifest::scalar(&slide.front_matter, "time"), "time")?;
+                Ok(if subset.contains(index as u32 + 1) {
+                    time
+                } else {
+                    Some(Duration::ZERO)
+                })
+            })
+            .collect::<std::result::Result<Vec<Option<Duration>>, DoughError>>()?;
+        timer.plan(duration, &times);
+        Ok(())
+    }
+
+    /// The key bindings of the project, from the `keys` section of `style.yml`.
+    /// A key bound to two commands is an error, so that it is caught before the presentation starts.
+    fn load_keymap(deck: &deck::Deck) -> std::result::Result<Keymap, Box<dyn Error>> {
+        let style_path = deck.dir.join("style.yml");
+        let settings = if style_path.exists() {
+            manifest::parse(&fs::read_to_string(&style_path)?)
+        } else {
+            manifest::Settings::new()
+        };
+        Ok(Keymap::load(&settings)
+            .map_err(|e| DoughError(format!("{}: {}", style_path.display(), e)))?)
+    }
+
+    /// The directories themes are looked up in: the templates next to the project, and the ones shipped with dough.
+    fn theme_dirs(&self) -> Vec<std::path::PathBuf> {
+        [
+            self.template
+                .parent()
+                .map(|templates| templates.to_path_buf()),
+            Some(std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("templates")),
+        ]
+        .into_iter()
+        .flatten()
+        .collect()
+    }
+
+    /// The style of a theme.
+    fn theme_style(
+        &self,
+        theme: &str,
+        variables: &HashMap<String, String>,
+    ) -> std::result::Result<HashMap<String, String>, Box<dyn Error>> {
+        let theme_path = self
+            .theme_dirs()
+            .into_iter()
+            .map(|templates| templates.join(theme).join("style.yml"))
+            .find(|style_path| style_path.exists())
+            .ok_or(DoughError(format!("Theme '{}' not found", theme)))?;
+
+        let theme_content = fs::read_to_string(theme_path)?;
+        Ok(parse_style(&vars::substitute(&theme_content, variables)))
+    }
+
+    /// The names of the themes, completed by the `theme` command.
+    fn themes(&self) -> Vec<String> {
+        let mut themes: Vec<String> = self
+            .theme_dirs()
+            .iter()
+            .filter_map(|templates| fs::read_dir(templates).ok())
+            .flatten()
+            .filter_map(|entry| entry.ok())
+            .filter(|entry| entry.path().join("style.yml").exists())
+            .filter_map(|entry| entry.file_name().to_str().map(|name| name.to_string()))
+            .collect();
+        themes.sort();
+        themes.dedup();
+        themes
+    }
+
+    /// Write the deck to a single markdown file, with the slides separated by `---` lines.
+    /// # Arguments
+    /// * `subset` - The slides presented, `{{ slide }}` and `{{ total }}` count them.
+    /// * `file` - The file to write, relative to the project directory.
+    ///   Defaults to the name of the project directory followed by `-export.md`.
+    /// # Returns
+    /// The path of the written file.
+    fn export(
+        deck: &deck::Deck,
+        subset: &Subset,
+        file: Option<&str>,
+    ) -> std::result::Result<std::path::PathBuf, Box<dyn Error>> {
+        let path = match file {
+            Some(file) => deck.dir.join(file),
+            None => {
+                let dir = deck.dir.canonicalize()?;
+                let name = dir
+                    .file_name()
+                    .and_then(|name| name.to_str())
+                    .unwrap_or("deck");
+                deck.dir.join(format!("{}-export.md", name))
+            }
+        };
+
+        let mut slides = Vec::new();
+        for index in 0..deck.slides.len() {
+            slides.push(
+                Self::slide_markdown(deck, subset, index)?
+                    .trim()
+                    .to_string(),
+            );
+        }
+        fs::write(&path, slides.join("\n\n---\n\n") + "\n")?;
+        Ok(path)
+    }
+
+    /// The source of a slide, with the speaker notes in its comments.
+    /// The code includes are expanded relative to the project directory, and the variables are substituted.
+    /// The front matter of the slide overrides the variables of the deck manifest.
+    fn slide_source(
+        deck: &deck::Deck,
+        subset: &Subset,
+        index: usize,
+    ) -> std::result::Result<String, Box<dyn Error>> {
+        let slide = &deck.slides[index];
+        let contents = include::expand_code_includes(&slide.markdown, &deck.dir)
+            .map_err(|e| DoughError(format!("{}: {}", slide.path.display(), e)))?;
+
+        let mut variables = Self::slide_variables(deck, subset, index);
+        vars::extend(&mut variables, &slide.front_matter);
+        Ok(vars::substitute(&contents, &variables))
+    }
+
+    /// The markdown of a slide, ready to be rendered, without the comments.
+    /// The table of contents is generated for a slide with the `<!-- toc -->` marker or `toc: true`.
+    fn slide_markdown(
+        deck: &deck::Deck,
+        subset: &Subset,
+        index: usize,
+    ) -> std::result::Result<String, Box<dyn Error>> {
+        let mut contents = Self::slide_source(deck, subset, index)?;
+        let toc = manifest::scalar(&deck.slides[index].front_matter, "toc") == Some("true");
+        if toc || toc::has_marker(&contents) {
+            contents = toc::expand(&contents, &Self::table_of_contents(deck, subset, index)?);
+        }
+        Ok(remove_comments(&contents))
+    }
+
+    /// The table of contents of the deck, for the slide at the given index.
+    /// Every slide is read for its title, the first heading of the slide.
+    fn table_of_contents(
+        deck: &deck::Deck,
+        subset: &Subset,
+        index: usize,
+    ) -> std::result::Result<String, Box<dyn Error>> {
+        let mut slides = Vec::new();
+        let mut entries = Vec::new();
+        for (i, slide) in deck.slides.iter().enumerate() {
+            let contents = remove_comments(&Self::slide_source(deck, subset, i)?);
+            entries.push(toc::Entry {
+                title: toc::title(&contents),
+                section: slide.section.clone(),
+            });
+            slides.push(contents);
+        }
+        Ok(toc::generate(&entries, &links::anchors(&slides), index))
+    }
+
+    /// Search the source of every slide of the deck, the code blocks and the speaker notes included.
+    /// The hidden slides are not searched.
+    fn search(
+        deck: &deck::Deck,
+        subset: &Subset,
+        query: &str,
+    ) -> std::result::Result<Search, Box<dyn Error>> {
+        let mut slides = Vec::new();
+        for index in 0..deck.slides.len() {
+            slides.push(if subset.contains(index as u32 + 1) {
+                Self::slide_source(deck, subset, index)?
+            } else {
+                String::new()
+            });
+        }
+        Ok(Search::new(query, &slides))
+    }
+
+    /// Resolve a link of a slide against the headings and the files of the deck.
+    fn resolve_link(
+        deck: &deck::Deck,
+        subset: &Subset,
+        url: &str,
+    ) -> std::result::Result<links::Target, Box<dyn Error>> {
+        let mut slides = Vec::new();
+        for index in 0..deck.slides.len() {
+            slides.push(Self::slide_markdown(deck, subset, index)?);
+        }
+        let files: Vec<&std::path::Path> = deck
+            .slides
+            .iter()
+            .map(|slide| slide.path.as_path())
+            .collect();
+        Ok(links::resolve(url, &links::anchors(&slides), &files))
+    }
+
+    /// Show the overview of the deck.
+    /// The slides are rendered without the box and the alignment, so that their start fits a card.
+    /// Only the slides presented are shown, the overview opens at the current slide or the next one presented.
+    /// # Returns
+    /// The number of the slide picked in the overview, if any.
+    fn overview(
+        deck: &deck::Deck,
+        subset: &Subset,
+        current: u32,
+        style_map: &HashMap<String, String>,
+    ) -> std::result::Result<Option<u32>, Box<dyn Error>> {
+        let mut card_style = style_map.clone();
+        for key in ["box", "horizontal_alignment", "vertical_alignment"] {
+            card_style.insert(key.to_string(), "false".to_string());
         }
 
-        drop(stdout);
+        let mut slides = Vec::new();
+        for slide in subset.slides() {
+            let contents = Self::slide_markdown(deck, subset, *slide as usize - 1)?;
+            slides.push(prettify::prettify(&contents, &card_style, 0)?);
+        }
 
-        return Ok((NavigationAction::None, line_number));
+        let selected = subset
+            .slides()
+            .iter()
+            .position(|slide| *slide >= current)
+            .unwrap_or(slides.len().saturating_sub(1));
+        Ok(overview::show(&slides, selected, style_map)?
+            .and_then(|index| subset.slides().get(index).copied()))
     }
 
     /// Run the code block in the slide.
@@ -358,6 +1009,83 @@ impl Project {
         }
     }
 
+    /// Load the deck of the project, with the slides of the section being presented.
+    fn load_deck(
+        &self,
+        options: &PresentOptions,
+    ) -> std::result::Result<deck::Deck, Box<dyn Error>> {
+        let mut deck = deck::load(&self.fs_path)?;
+        if let Some(section) = &options.section {
+            deck.select_section(section)?;
+        }
+        Ok(deck)
+    }
+
+    /// The directory of the project, which holds `style.yml` and `deck.yml`.
+    /// A single file deck uses the directory the file is in.
+    fn project_dir(&self) -> std::path::PathBuf {
+        if self.fs_path.is_file() {
+            return self
+                .fs_path
+                .parent()
+                .unwrap_or(std::path::Path::new("."))
+                .to_path_buf();
+        }
+        self.fs_path.clone()
+    }
+
+    /// Load the style map of a deck.
+    /// The style of the theme set in the deck manifest is used as a base, which the style.yml of the project overrides.
+    /// The runtimes of the manifest override the runtimes of the style.
+    /// # Arguments
+    /// * `deck` - The deck being presented.
+    /// * `variables` - The variables substituted in the style.
+    /// # Returns
+    /// A result containing the style map.
+    fn load_style(
+        &self,
+        deck: &deck::Deck,
+        variables: &HashMap<String, String>,
+    ) -> std::result::Result<HashMap<String, String>, Box<dyn Error>> {
+        let mut style_map = HashMap::new();
+
+        if let Some(theme) = manifest::scalar(&deck.settings, "theme") {
+            style_map.extend(self.theme_style(theme, variables)?);
+        }
+
+        let style_path = deck.dir.join("style.yml");
+        if style_path.exists() {
+            let style_content = fs::read_to_string(style_path)?;
+            style_map.extend(parse_style(&vars::substitute(&style_content, variables)));
+        }
+
+        if let Some(runtimes) = manifest::map(&deck.settings, "runtimes") {
+            style_map.extend(runtimes.clone());
+        }
+
+        Ok(style_map)
+    }
+
+    /// End the presentation.
+    /// A rehearsal writes its report to the project directory, and prints it.
+    fn finish(
+        rehearsal: Option<&Rehearsal>,
+        deck: &deck::Deck,
+    ) -> std::result::Result<(), Box<dyn Error>> {
+        print!("{}", termion::cursor::Show);
+        print!("{}", termion::clear::All);
+        if let Some(rehearsal) = rehearsal {
+            let (table, paths) = rehearsal.finish(&deck.dir)?;
+            print!("{}", termion::cursor::Goto(1, 1));
+            println!("{}\n", table);
+            for path in paths {
+                Logger::new().success(format!("Wrote {}", path.display()));
+            }
+        }
+        println!("Thank you :)");
+        Ok(())
+    }
+
     /// This clears the terminal.
     fn clear() {
         let mut stdout = stdout();
@@ -367,33 +1095,125 @@ impl Project {
     /// Present a project in terminal mode.
     /// # Arguments
     /// * `self` - The project instance.
+    /// * `options` - The options of the presentation.
     /// # Returns
     /// A result indicating whether the project was presented successfully or not.
-
-    pub fn present_term(self: &Self) -> std::result::Result<(), Box<dyn Error>> {
+    pub fn present_term(
+        &self,
+        options: &PresentOptions,
+    ) -> std::result::Result<(), Box<dyn Error>> {
         // The custom Paris logger, used to log the progress of the presentation.
         let mut log = Logger::new();
-        // Used to determine whether to highlight the code or scroll.
-        let mut highlight = true;
-        // Used to determine whether to render a new slide or not. Used for scrolling.
-        let mut render = true;
-        let mut current_slide = 1;
-
-        // The number of lines to be rendered.
-        let mut lines: u32 = 1;
+        // The tags and the numbers of the slides to present, the other slides are hidden.
+        let filter = Filter {
+            only_tags: options
+                .only_tags
+                .as_deref()
+                .map(subset::parse_tags)
+                .unwrap_or_default(),
+            skip_tags: options
+                .skip_tags
+                .as_deref()
+                .map(subset::parse_tags)
+                .unwrap_or_default(),
+            slides: match &options.slides {
+                Some(slides) => subset::parse_ranges(slides).map_err(DoughError)?,
+                None => Vec::new(),
+            },
+        };
+        // The deck is loaded once, and again when the presentation is reloaded.
+        let mut deck = self.load_deck(options)?;
+        let mut subset = Subset::new(&deck, &filter).map_err(DoughError)?;
+        // The navigation state of the presentation: the current slide, the scrolling and the history of jumps.
+        // Without a start, the presentation opens at the first slide of the subset.
+        let mut presentation = Presentation::new(options.start.unwrap_or(subset.first()));
+        presentation.kiosk = options.kiosk;
+        if options.resume {
+            match resume::load(&self.fs_path)? {
+                Some(position) if position.section == options.section => {
+                    position.restore(&mut presentation)
+                }
+                Some(_) => {
+                    log.warn("The last position is in another section, starting over");
+                }
+                None => {
+                    log.warn("No position to resume, starting over");
+                }
+            }
+        }
+        // A start past the end of the deck opens at the last slide, as does a position left on a removed slide.
+        // This is checked once, running off the end later ends the presentation.
+        let total_slides = deck.slides.len() as u32;
+        if total_slides > 0 && presentation.current_slide > total_slides {
+            presentation.go_to(total_slides);
+        }
+        // The time of the talk, shown in the footer against the plan of the deck.
+        let mut timer = Timer::new();
+        // The search of the deck, its matches are highlighted until a new search is made.
+        let mut search: Option<Search> = None;
+        // A message to show on the next render, like the number of matches of a search.
+        let mut status: Option<String> = None;
+        // The theme and the styles set from the command line, they take over the style config.
+        let mut theme_override: Option<String> = None;
+        let mut overrides: HashMap<String, String> = HashMap::new();
+        // Whether the speaker notes are shown below the slides.
+        let mut show_notes = false;
+        // The times of the slides, when the presentation is a rehearsal.
+        let mut rehearsal = options.rehearse.then(Rehearsal::new);
+        // The followers of the presentation, when it is shared.
+        let share = match &options.share {
+            Some(address) => {
+                let share = Share::start(address).map_err(|e| {
+                    DoughError(format!(
+                        "Could not share the presentation on {}: {}",
+                        address, e
+                    ))
+                })?;
+                status = Some(format!("Sharing on {}", address).dimmed().to_string());
+                Some(share)
+            }
+            None => None,
+        };
+        // The remote control, when it is served.
+        let remote = match &options.remote {
+            Some(address) => {
+                let remote = Remote::start(address).map_err(|e| {
+                    DoughError(format!("Could not serve the remote on {}: {}", address, e))
+                })?;
+                // The address with the token is printed before the terminal is taken over, and kept off the slides,
+                // as the slides are on the projector for everyone to read.
+                eprintln!("Remote on {}", remote.url(address));
+                let line = format!("Remote on http://{}", address).dimmed().to_string();
+                status = Some(match status.take() {
+                    Some(sharing) => format!("{}  {}", sharing, line),
+                    None => line,
+                });
+                Some(remote)
+            }
+            None => None,
+        };
 
         // Check if the project directory has style.yml file
-        let style_path = self.fs_path.join("style.yml");
-        if !style_path.exists() {
+        // A deck with a theme can go without one, the style of the theme is used instead.
+        let style_path = self.project_dir().join("style.yml");
+        let theme = manifest::scalar(&deck.settings, "theme").is_some();
+        if !style_path.exists() && !theme {
             // If the style.yml file is not found, the default style config is used to build the style.yml file.
             log.warn("Style config not found. Using default styles");
-            let res = utils::create_style(self.fs_path.clone());
+            let res = utils::create_style(self.project_dir());
             match res {
                 Ok(_) => {}
                 Err(e) => println!("Error creating style file: {}", e),
             }
         }
 
+        // The key bindings and the times of the talk are checked before the terminal is taken over,
+        // so that a mistake is reported plainly.
+        let mut keymap = Self::load_keymap(&deck)?;
+        Self::plan(&deck, &subset, &mut timer)?;
+        // Set by a reload, the deck, the key bindings and the plan are read again before the next render.
+        let mut reload = false;
+
         // The loop is used to present the slides one by one.
         // The loop is exited when the user exits the presentation.
         // It handles the navigation actions.
@@ -401,102 +1221,459 @@ impl Project {
         loop {
             Self::clear();
             print!("{}", termion::cursor::Hide);
-            let file_path = self.fs_path.join(format!("{}.md", current_slide));
-
-            if !file_path.exists() {
-                if current_slide == 1 {
-                    return Err(Box::new(DoughError(
-                        "No slides found in the project".into(),
-                    )));
-                }
-                print!("{}", termion::cursor::Show);
-                print!("{}", termion::clear::All);
-                println!("Thank you :)");
-                exit(0)
+            // Changes to the slides, the key bindings and the times are picked up on a reload.
+            if reload {
+                deck = self.load_deck(options)?;
+                subset = Subset::new(&deck, &filter).map_err(DoughError)?;
+                keymap = Self::load_keymap(&deck)?;
+                Self::plan(&deck, &subset, &mut timer)?;
+                reload = false;
             }
-            // The contents of the file are read and the comments are removed.
-            let file_contents = fs::read_to_string(&file_path)?;
-            let contents = remove_comments(&file_contents);
+            let total_slides = deck.slides.len() as u32;
 
-            // The style map is used to describe the style of the slides.
-            let style_content = fs::read_to_string(self.fs_path.join("style.yml"))?;
-            let style_map: HashMap<String, String> = style_content
-                .lines()
-                .filter_map(|line| {
-                    let mut parts = line.splitn(2, ':');
-                    Some((
-                        parts.next()?.trim().to_string(),
-                        parts.next()?.trim().to_string(),
+            let slide = match deck.slides.get(presentation.current_slide as usize - 1) {
+                Some(slide) => slide,
+                None => {
+                    if total_slides == 0 {
+                        return Err(Box::new(DoughError(
+                            "No slides found in the project".into(),
+                        )));
+                    }
+                    // A looping deck starts over, and a kiosk stays on its last slide.
+                    if options.wrap {
+                        presentation.go_to(subset.first());
+                        continue;
+                    }
+                    if presentation.kiosk {
+                        presentation.go_to(subset.last());
+                        continue;
+                    }
+                    Self::finish(rehearsal.as_ref(), &deck)?;
+                    exit(0)
+                }
+            };
+
+            // The position is kept to resume from, a state directory that can not be written to does not stop the presentation.
+            let _ = resume::save(&self.fs_path, options.section.as_deref(), &presentation);
+
+            // A slide with a `duration` advances by itself, the other slides after the time of `--auto`.
+            presentation.advance = match manifest::scalar(&slide.front_matter, "duration") {
+                Some(value) => Some(timer::parse_duration(value).ok_or_else(|| {
+                    DoughError(format!(
+                        "The duration '{}' of slide {} is not a duration, e.g. 15s or 1m",
+                        value, presentation.current_slide
                     ))
-                })
-                .collect();
+                })?),
+                None => options.auto,
+            };
+
+            // The slide is read with its code includes expanded and its variables substituted.
+            let contents =
+                Self::slide_markdown(&deck, &subset, presentation.current_slide as usize - 1)?;
+            let style_variables =
+                Self::slide_variables(&deck, &subset, presentation.current_slide as usize - 1);
+
+            // The style map is used to describe the style of the slides.
+            let mut style_map = self.load_style(&deck, &style_variables)?;
+            if let Some(theme) = &theme_override {
+                style_map.extend(self.theme_style(theme, &style_variables)?);
+            }
+            style_map.extend(overrides.clone());
+            Self::plan_footer(&mut style_map, &timer);
+            timer.enter(presentation.current_slide as usize - 1);
+
+            // The speaker notes are shown on the last lines, unless there is a message to show.
+            if show_notes && status.is_none() {
+                let notes = utils::speaker_notes(&Self::slide_source(
+                    &deck,
+                    &subset,
+                    presentation.current_slide as usize - 1,
+                )?);
+                status = Some(if notes.is_empty() {
+                    "No notes".dimmed().to_string()
+                } else {
+                    notes.join("\n")
+                });
+            }
 
             // The navigation actions are handled here.
             // TODO: Pass a mutable reference of the lines to be rendered to the render_term function, instead of returning it and updating it here.
 
-            match Self::render_term(
+            prettify::set_code_step(presentation.code_step);
+            prettify::set_search(search.as_ref().map(|search| search.pattern.clone()));
+            prettify::set_link_focus(presentation.link_focus);
+            let current_slide = presentation.current_slide;
+            let progress = Progress {
+                slide: subset.position(current_slide),
+                total: subset.len(),
+                section: slide.section.as_deref(),
+                title: manifest::scalar(&deck.settings, "title"),
+                timer: &timer,
+                status: status.take(),
+            };
+            // The followers are sent the slide as it is about to be shown.
+            if let Some(share) = &share {
+                share.publish(&State {
+                    slide: progress.slide.unwrap_or(0),
+                    total: progress.total,
+                    code_step: presentation.code_step,
+                    lines: presentation.lines,
+                    highlight: presentation.highlight,
+                    render: presentation.render,
+                    style_map: style_map.clone(),
+                    markdown: contents.clone(),
+                    footer: Self::footer_text(&style_map, &progress)
+                        .map(|text| timer.fill(&text))
+                        .unwrap_or_default(),
+                });
+            }
+            // The remote is told of the slide, with its notes and the time of the talk.
+            if let Some(remote) = &remote {
+                remote.update(Status {
+                    slide: current_slide,
+                    total: total_slides,
+                    position: progress.slide,
+                    presented: progress.total,
+                    title: toc::title(&contents),
+                    deck: progress.title.map(|title| title.to_string()),
+                    section: progress.section.map(|section| section.to_string()),
+                    notes: utils::speaker_notes(&Self::slide_source(
+                        &deck,
+                        &subset,
+                        current_slide as usize - 1,
+                    )?),
+                    kiosk: presentation.kiosk,
+                    timer: timer.clone(),
+                });
+            }
+            let shown = std::time::Instant::now();
+            let (action, lines, prompted) = Self::render_term(
                 self,
                 &contents,
                 &style_map,
-                highlight,
-                render,
-                &mut lines,
-                current_slide,
-            )? {
+                &mut presentation,
+                &keymap,
+                progress,
+            )?;
+            // The step is clamped to the steps of the rendered slide, a slide entered backwards starts at its last step.
+            presentation.code_step = presentation
+                .code_step
+                .min(prettify::get_code_steps().saturating_sub(1));
+            if let Some(rehearsal) = &mut rehearsal {
+                rehearsal.record(
+                    current_slide as usize - 1,
+                    presentation.code_step,
+                    toc::title(&contents),
+                    // The time spent at the prompts is left out, as the overview and the blank screens are.
+                    shown.elapsed().saturating_sub(prompted),
+                );
+            }
+
+            match (action, lines) {
                 (NavigationAction::Next, _new_lines_value) => {
-                    // A fresh slide is rendered.
-                    render = true;
-                    current_slide += 1;
-                    // scrolling starts from the bottom
-                    lines = 1;
+                    presentation.render = true;
+                    if presentation.code_step + 1 < prettify::get_code_steps() {
+                        // The code blocks are stepped through before leaving the slide.
+                        presentation.code_step += 1;
+                        continue;
+                    }
+                    // A fresh slide is rendered, the hidden slides are stepped over.
+                    match subset.next(presentation.current_slide) {
+                        Some(slide) => presentation.go_to(slide),
+                        None => presentation.go_to(total_slides + 1),
+                    }
                 }
                 (NavigationAction::Previous, _new_lines_value) => {
+                    presentation.render = true;
+                    if presentation.code_step > 0 {
+                        presentation.code_step -= 1;
+                        continue;
+                    }
                     // A fresh slide is rendered.
-                    render = true;
-                    if current_slide > 1 {
-                        current_slide -= 1;
+                    if let Some(slide) = subset.previous(presentation.current_slide) {
+                        presentation.go_to(slide);
+                        presentation.code_step = usize::MAX;
+                    } else if options.wrap {
+                        presentation.go_to(subset.last());
+                        presentation.code_step = usize::MAX;
                     }
-                    // scrolling starts from the bottom
-                    lines = 1;
                 }
                 (NavigationAction::ScrollUp, new_lines_value) => {
                     // The slide is scrolled up, or the lines are highlighted.
-                    render = false;
-                    lines = new_lines_value;
-                    lines += 1
+                    presentation.render = false;
+                    presentation.lines = new_lines_value + 1;
                 }
                 (NavigationAction::ScrollDown, new_lines_value) => {
                     // The slide is scrolled down, or the lines are highlighted.
-                    render = false;
-                    lines = new_lines_value;
-                    if lines != 0 {
-                        lines -= 1;
-                    }
+                    presentation.render = false;
+                    presentation.lines = new_lines_value.saturating_sub(1);
                 }
                 (NavigationAction::ToggleHighlight, new_lines_value) => {
-                    if highlight {
-                        lines = 1;
+                    if presentation.highlight {
+                        presentation.lines = 1;
                     } else {
-                        lines = new_lines_value;
+                        presentation.lines = new_lines_value;
                     }
 
                     // The slide is toggled between highlighting and scrolling.
-                    highlight = !highlight;
+                    presentation.highlight = !presentation.highlight;
+                }
+                (NavigationAction::NextSection, _new_lines_value) => {
+                    // The first slide presented of the next section is rendered, the last section has nowhere to go.
+                    if let Some(slide) = subset.next_section(&deck, presentation.current_slide) {
+                        presentation.jump_to(slide);
+                    }
+                }
+                (NavigationAction::PreviousSection, _new_lines_value) => {
+                    if let Some(slide) = subset.previous_section(&deck, presentation.current_slide)
+                    {
+                        presentation.jump_to(slide);
+                    }
+                }
+                (NavigationAction::GoTo(slide), _new_lines_value) => {
+                    // A number past the end of the deck goes to the last slide.
+                    if slide > 0 {
+                        presentation.jump_to(slide.min(total_slides));
+                    }
+                }
+                (NavigationAction::First, _new_lines_value) => presentation.jump_to(subset.first()),
+                (NavigationAction::Last, _new_lines_value) => presentation.jump_to(subset.last()),
+                (NavigationAction::Back, _new_lines_value) => presentation.back(),
+                (NavigationAction::Search(query), _new_lines_value) => {
+                    presentation.render = true;
+                    if query.is_empty() {
+                        search = None;
+                        continue;
+                    }
+                    // The search starts at the current slide.
+                    let mut found = Self::search(&deck, &subset, &query)?;
+                    if let Some(index) = found.first_from(presentation.current_slide as usize - 1) {
+                        presentation.jump_to(index as u32 + 1);
+                    }
+                    status = Some(found.status());
+                    search = Some(found);
+                }
+                (NavigationAction::RunCode(c_num), _new_lines_value) => {
+                    Self::run_code_in_background(c_num, &style_map);
+                }
+                (NavigationAction::Theme(name), _new_lines_value) => {
+                    // The theme is checked before it is used, a missing theme would end the presentation.
+                    match self.theme_style(&name, &style_variables) {
+                        Ok(_) => theme_override = Some(name),
+                        Err(e) => status = Some(e.to_string()),
+                    }
+                }
+                (NavigationAction::Set(key, value), _new_lines_value) => {
+                    if !style_map.contains_key(&key) {
+                        status = Some(format!("Unknown style '{}'", key));
+                        continue;
+                    }
+                    overrides.insert(key, value);
+                }
+                (NavigationAction::Notes, _new_lines_value) => show_notes = !show_notes,
+                (NavigationAction::Export(file), _new_lines_value) => {
+                    status = Some(match Self::export(&deck, &subset, file.as_deref()) {
+                        Ok(path) => {
+                            format!("Exported {} slides to {}", total_slides, path.display())
+                        }
+                        Err(e) => format!("Could not export the deck: {}", e),
+                    });
+                }
+                (NavigationAction::Message(message), _new_lines_value) => status = Some(message),
+                (NavigationAction::NextLink, _new_lines_value) => {
+                    let links = prettify::get_links().len();
+                    if links > 0 {
+                        presentation.link_focus = Some(
+                            presentation
+                                .link_focus
+                                .map_or(0, |focus| (focus + 1) % links),
+                        );
+                    }
+                }
+                (NavigationAction::PreviousLink, _new_lines_value) => {
+                    let links = prettify::get_links().len();
+                    if links > 0 {
+                        presentation.link_focus = Some(match presentation.link_focus {
+                            Some(0) | None => links - 1,
+                            Some(focus) => focus - 1,
+                        });
+                    }
+                }
+                (NavigationAction::Blank(screen), _new_lines_value) => {
+                    screen::show(&screen, &style_map)?
+                }
+                (NavigationAction::FollowLink(link), _new_lines_value) => {
+                    // A link is picked by its number, or it is the focused link.
+                    let url = match link
+                        .map(|link| link.saturating_sub(1))
+                        .or(presentation.link_focus)
+                        .and_then(|focus| prettify::get_links().get(focus).cloned())
+                    {
+                        Some(url) => url,
+                        None => continue,
+                    };
+                    match Self::resolve_link(&deck, &subset, &url)? {
+                        // A link to a hidden slide is not followed, the slide is left out of this talk.
+                        links::Target::Slide(index) if !subset.contains(index as u32 + 1) => {
+                            status = Some(format!("The slide of '{}' is hidden", url));
+                        }
+                        links::Target::Slide(index) => presentation.jump_to(index as u32 + 1),
+                        links::Target::External(url) => {
+                            if let Err(e) = links::open_external(&url) {
+                                status = Some(e.to_string());
+                            }
+                        }
+                        links::Target::Missing(url) => {
+                            status = Some(format!("No slide found for '{}'", url));
+                        }
+                    }
+                }
+                (NavigationAction::NextMatch, _new_lines_value) => {
+                    presentation.render = true;
+                    if let Some(found) = search.as_mut() {
+                        if let Some(index) = found.next() {
+                            presentation.jump_to(index as u32 + 1);
+                        }
+                        status = Some(found.status());
+                    }
+                }
+                (NavigationAction::PreviousMatch, _new_lines_value) => {
+                    presentation.render = true;
+                    if let Some(found) = search.as_mut() {
+                        if let Some(index) = found.previous() {
+                            presentation.jump_to(index as u32 + 1);
+                        }
+                        status = Some(found.status());
+                    }
+                }
+                (NavigationAction::Overview, _new_lines_value) => {
+                    match Self::overview(&deck, &subset, presentation.current_slide, &style_map)? {
+                        Some(slide) => presentation.jump_to(slide),
+                        None => presentation.render = true,
+                    }
                 }
                 (NavigationAction::Refresh, _new_lines_value) => {
                     // Refreshes the slide after a change is made to the MD file.
-                    render = true;
-                    lines = 1;
+                    reload = true;
+                    presentation.render = true;
+                    presentation.lines = 1;
                 }
                 (NavigationAction::Exit, _new_lines_value) => {
                     // The presentation is exited.
-                    print!("{}", termion::cursor::Show);
-                    print!("{}", termion::clear::All);
-                    println!("Thank you :)");
+                    Self::finish(rehearsal.as_ref(), &deck)?;
                     exit(0);
                 }
+                (NavigationAction::Unlock, _new_lines_value) => {
+                    presentation.kiosk = false;
+                    status = Some("Unlocked".dimmed().to_string());
+                }
                 (NavigationAction::None, _new_lines_value) => {}
             }
         }
     }
 }
+
+/// Follow a shared presentation, the slide of the presenter is rendered at the size of this terminal.
+/// q, Esc or Ctrl + C stops following.
+/// # Arguments
+/// * `address` - The address the presentation is shared on.
+pub fn follow(address: &str) -> std::result::Result<(), Box<dyn Error>> {
+    let mut reader = share::connect(address)
+        .map_err(|e| DoughError(format!("Could not follow {}: {}", address, e)))?;
+
+    // The states are read in a thread of their own, so that the keys are read while waiting for the presenter.
+    let (sender, receiver) = std::sync::mpsc::channel();
+    thread::spawn(move || loop {
+        let state = State::read(&mut reader).ok().flatten();
+        let gone = state.is_none();
+        if sender.send(state).is_err() || gone {
+            break;
+        }
+    });
+
+    let mut stdout = stdout().into_raw_mode()?;
+    Project::clear();
+    write!(
+        stdout,
+        "{}{}",
+        termion::cursor::Hide,
+        format!("Waiting for the presenter on {}", address).dimmed()
+    )?;
+    stdout.flush()?;
+
+    let mut state: Option<State> = None;
+    let mut size = termion::terminal_size()?;
+    let ended = loop {
+        let deadline = std::time::Instant::now() + Duration::from_millis(100);
+        match input::key(Some(deadline)) {
+            Wait::Key(Key::Char('q' | 'Q') | Key::Esc | Key::Ctrl('c')) => break false,
+            // Without a terminal to read the keys of, the follower only waits for the presenter.
+            Wait::Closed => {
+                thread::sleep(deadline.saturating_duration_since(std::time::Instant::now()))
+            }
+            _ => {}
+        }
+
+        let mut changed = false;
+        let mut gone = false;
+        while let Ok(message) = receiver.try_recv() {
+            match message {
+                Some(message) => {
+                    state = Some(message);
+                    changed = true;
+                }
+                None => gone = true,
+            }
+        }
+        if gone {
+            break true;
+        }
+        // The slide is drawn again when the terminal is resized.
+        let new_size = termion::terminal_size()?;
+        if new_size != size {
+            size = new_size;
+            changed = true;
+        }
+        if let (true, Some(state)) = (changed, &state) {
+            // The slide is printed with the terminal out of raw mode, as it is when presenting.
+            stdout.suspend_raw_mode()?;
+            draw_followed(state)?;
+            stdout.activate_raw_mode()?;
+        }
+    };
+
+    write!(stdout, "{}{}", termion::cursor::Show, termion::clear::All)?;
+    drop(stdout);
+    if ended {
+        println!("The presentation has ended");
+    }
+    Ok(())
+}
+
+/// Draw the slide of a followed presentation.
+fn draw_followed(state: &State) -> std::result::Result<(), Box<dyn Error>> {
+    Project::clear();
+    print!("{}", termion::cursor::Hide);
+    prettify::set_code_step(state.code_step);
+    prettify::set_search(None);
+    prettify::set_link_focus(None);
+    let lines = if state.highlight { state.lines } else { 0 };
+    let slide = prettify::prettify(&state.markdown, &state.style_map, lines)?;
+    if !state.highlight && !state.render {
+        print!("{}", remove_last_n_lines(&slide, state.lines));
+    } else {
+        print!("{}", slide);
+    }
+    if !state.footer.is_empty() {
+        print!(
+            "{}",
+            prettify::render_footer(
+                &state.footer,
+                state.slide as f32 / state.total.max(1) as f32,
+                &state.style_map
+            )
+        );
+    }
+    stdout().flush()?;
+    Ok(())
+}
@@ -0,0 +1,216 @@
+//! Links resolves the links of a slide to the slides of the deck.
+//! An internal link is a heading anchor like `#topic-1`, a slide file like `2.md`, or both: `2.md#topic-2`.
+//! The anchor of a heading is its text in lower case, with the spaces turned into dashes
+//! and the punctuation removed, the way GitHub does it.
+//! Any other link is external, and opened with the default application of the system.
+
+use crate::include::{is_closing_fence, open_fence};
+
+use std::collections::HashMap;
+use std::error::Error;
+use std::path::Path;
+use std::process::{Command, Stdio};
+
+use lazy_static::lazy_static;
+use regex::Regex;
+
+lazy_static! {
+    /// This is used to match an inline link or image in a heading, e.g. [text](url)
+    static ref INLINE_LINK_RE: Regex = Regex::new(r"!?\[([^\]]*)\]\([^)]*\)").unwrap();
+
+    /// This is used to match the custom alignment markers, e.g. $[c]$
+    static ref ALIGNMENT_RE: Regex = Regex::new(r"\$\[[a-z]\]\$").unwrap();
+}
+
+/// Where a link leads.
+#[derive(Debug, PartialEq)]
+pub enum Target {
+    /// A slide of the deck, by index.
+    Slide(usize),
+    /// A link outside of the deck.
+    External(String),
+    /// An internal link that matches no slide.
+    Missing(String),
+}
+
+/// The anchors of the headings of a deck, mapped to the index of the slide they are on.
+/// A heading repeated in the deck gets a numbered anchor, `#usage-1` for the second `## Usage`.
+/// # Arguments
+/// * `slides` - The markdown of each slide of the deck.
+pub fn anchors(slides: &[String]) -> HashMap<String, usize> {
+    let mut anchors = HashMap::new();
+    let mut seen: HashMap<String, usize> = HashMap::new();
+
+    for (index, slide) in slides.iter().enumerate() {
+        let mut fence: Option<String> = None;
+        for line in slide.lines() {
+            if let Some(open) = &fence {
+                if is_closing_fence(line, open) {
+                    fence = None;
+                }
+                continue;
+            }
+            if let Some((open, _)) = open_fence(line) {
+                fence = Some(open);
+                continue;
+            }
+
+            let text = match heading_text(line) {
+                Some(text) => text,
+                None => continue,
+            };
+            let anchor = slug(&text);
+            let count = seen.entry(anchor.clone()).or_insert(0);
+            let anchor = if *count == 0 {
+                anchor
+            } else {
+                format!("{}-{}", anchor, count)
+            };
+            *count += 1;
+            anchors.entry(anchor).or_insert(index);
+        }
+    }
+
+    anchors
+}
+
+/// Resolve a link of a slide.
+/// # Arguments
+/// * `url` - The url of the link.
+/// * `anchors` - The anchors of the deck.
+/// * `files` - The file of each slide of the deck.
+pub fn resolve(url: &str, anchors: &HashMap<String, usize>, files: &[&Path]) -> Target {
+    if url.contains("://") || url.starts_with("mailto:") {
+        return Target::External(url.to_string());
+    }
+
+    let (file, anchor) = match url.split_once('#') {
+        Some((file, anchor)) => (file, Some(anchor)),
+        None => (url, None),
+    };
+
+    if let Some(anchor) = anchor.filter(|anchor| !anchor.is_empty()) {
+        if let Some(&index) = anchors.get(&slug(anchor)) {
+            return Target::Slide(index);
+        }
+    }
+    if !file.is_empty() {
+        let name = Path::new(file).file_name();
+        if let Some(index) = files.iter().position(|path| path.file_name() == name) {
+            return Target::Slide(index);
+        }
+    }
+
+    Target::Missing(url.to_string())
+}
+
+/// Open an external link with the default application of the system.
+pub fn open_external(url: &str) -> Result<(), Box<dyn Error>> {
+    let opener = if cfg!(target_os = "macos") {
+        "open"
+    } else {
+        "xdg-open"
+    };
+    Command::new(opener)
+        .arg(url)
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()
+        .map_err(|e| format!("Could not open '{}' with {}: {}", url, opener, e))?;
+    Ok(())
+}
+
+/// The text of a heading line, without its markers and its inline formatting.
+fn heading_text(line: &str) -> Option<String> {
+    let trimmed = line.trim_start();
+    // A heading can be indented by up to three spaces.
+    if line.len() - trimmed.len() > 3 {
+        return None;
+    }
+    let hashes = trimmed.chars().take_while(|&c| c == '#').count();
+    if hashes == 0 || hashes > 6 {
+        return None;
+    }
+    let rest = &trimmed[hashes..];
+    if !rest.is_empty() && !rest.starts_with(' ') && !rest.starts_with('\t') {
+        return None;
+    }
+
+    let text = rest.trim().trim_end_matches('#').trim();
+    let text = strip_alignment(text);
+    let text = INLINE_LINK_RE.replace_all(&text, "$1");
+    Some(text.replace(['*', '`', '~'], ""))
+}
+
+/// Remove the custom alignment markers of a text, e.g. `$[c]$`.
+pub fn strip_alignment(text: &str) -> String {
+    ALIGNMENT_RE.replace_all(text, "").to_string()
+}
+
+/// The anchor of a heading text: `Topic 1` is `topic-1`.
+pub fn slug(text: &str) -> String {
+    text.trim()
+        .to_lowercase()
+        .chars()
+        .filter_map(|c| match c {
+            ' ' => Some('-'),
+            c if c.is_alphanumeric() || c == '-' || c == '_' => Some(c),
+            _ => None,
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn slugs_headings_the_way_github_does() {
+        assert_eq!(slug("Topic 1"), "topic-1");
+        assert_eq!(slug("  What's new?  "), "whats-new");
+        assert_eq!(slug("snake_case & kebab-case"), "snake_case--kebab-case");
+        assert_eq!(slug("Über Rust"), "über-rust");
+    }
+
+    #[test]
+    fn reads_the_text_of_headings() {
+        assert_eq!(
+            heading_text("## **Bold** [link](https://a.b) `code` ##"),
+            Some("Bold link code".to_string())
+        );
+        assert_eq!(strip_alignment("$[c]$Centered"), "Centered");
+        assert_eq!(heading_text("#hashtag"), None);
+        assert_eq!(heading_text("    # indented code"), None);
+        assert_eq!(heading_text("####### seven"), None);
+    }
+
+    #[test]
+    fn numbers_repeated_headings() {
+        let slides = vec![
+            "# Usage\n```sh\n# not a heading\n```".to_string(),
+            "## Usage".to_string(),
+        ];
+        let anchors = anchors(&slides);
+        assert_eq!(anchors.get("usage"), Some(&0));
+        assert_eq!(anchors.get("usage-1"), Some(&1));
+        assert_eq!(anchors.get("not-a-heading"), None);
+    }
+
+    #[test]
+    fn resolves_anchors_files_and_external_links() {
+        let anchors = anchors(&["# Intro".to_string(), "# Topic 2".to_string()]);
+        let files = [Path::new("deck/1.md"), Path::new("deck/2.md")];
+        assert_eq!(resolve("#topic-2", &anchors, &files), Target::Slide(1));
+        assert_eq!(resolve("2.md", &anchors, &files), Target::Slide(1));
+        assert_eq!(resolve("1.md#topic-2", &anchors, &files), Target::Slide(1));
+        assert_eq!(
+            resolve("#nowhere", &anchors, &files),
+            Target::Missing("#nowhere".to_string())
+        );
+        assert_eq!(
+            resolve("https://rust-lang.org", &anchors, &files),
+            Target::External("https://rust-lang.org".to_string())
+        );
+    }
+}
@@ -1,5 +1,7 @@
 use clap::{App, Arg, SubCommand};
-use dough::Project;
+use dough::{
+    follow, parse_duration, PresentOptions, Project, DEFAULT_ADDRESS, DEFAULT_REMOTE_ADDRESS,
+};
 use paris::Logger;
 use std::env;
 use std::process;
@@ -7,8 +9,8 @@ use std::process;
 /// The main function of the program.
 ///
 /// This function initializes the paris logger, parses command-line arguments using `clap`, and
-/// dispatches commands based on the provided subcommands. It handles the 'new' and 'present'
-/// subcommands, creating or presenting projects accordingly, and prints help information
+/// dispatches commands based on the provided subcommands. It handles the 'new', 'present',
+/// 'rehearse' and 'follow' subcommands, creating or presenting projects accordingly, and prints help information
 /// if no valid subcommand is provided.
 
 fn main() {
@@ -53,6 +55,120 @@ fn main() {
                         .possible_values(&["html", "term"])
                         .default_value("term")
                         .help("Choose the mode of presentation: html or term. Currently we only support term"),
+                )
+                .arg(
+                    Arg::with_name("start")
+                        .long("start")
+                        .takes_value(true)
+                        .help("Start the presentation at the given slide"),
+                )
+                .arg(
+                    Arg::with_name("section")
+                        .long("section")
+                        .takes_value(true)
+                        .help("Present the slides of one section only, e.g. --section basics for the 01-basics directory"),
+                )
+                .arg(
+                    Arg::with_name("only-tags")
+                        .long("only-tags")
+                        .takes_value(true)
+                        .help("Present only the slides with one of the tags, e.g. --only-tags core,demo"),
+                )
+                .arg(
+                    Arg::with_name("skip-tags")
+                        .long("skip-tags")
+                        .takes_value(true)
+                        .help("Hide the slides with one of the tags, e.g. --skip-tags appendix"),
+                )
+                .arg(
+                    Arg::with_name("slides")
+                        .long("slides")
+                        .takes_value(true)
+                        .help("Present only the given slides, e.g. --slides 3-9,12"),
+                )
+                .arg(
+                    Arg::with_name("auto")
+                        .long("auto")
+                        .takes_value(true)
+                        .help("Advance to the next slide on its own after a time, e.g. --auto 15s"),
+                )
+                .arg(
+                    Arg::with_name("loop")
+                        .long("loop")
+                        .help("Start over at the first slide after the last one"),
+                )
+                .arg(
+                    Arg::with_name("share")
+                        .long("share")
+                        .takes_value(true)
+                        .min_values(0)
+                        .value_name("address")
+                        .help("Share the presentation for `dough follow`, on a TCP address or a unix socket, 127.0.0.1:7373 by default"),
+                )
+                .arg(
+                    Arg::with_name("remote")
+                        .long("remote")
+                        .takes_value(true)
+                        .min_values(0)
+                        .value_name("address")
+                        .help("Serve a remote control and a JSON state over HTTP, on 127.0.0.1:7374 by default"),
+                )
+                .arg(
+                    Arg::with_name("resume")
+                        .long("resume")
+                        .conflicts_with("start")
+                        .help("Open the presentation where it was last left"),
+                )
+                .arg(
+                    Arg::with_name("kiosk")
+                        .long("kiosk")
+                        .help("Ignore every key but the unlock sequence, Ctrl+X Ctrl+U by default"),
+                ),
+        )
+        .subcommand(
+            // Presents a project in terminal mode, and records the time of each slide.
+            SubCommand::with_name("rehearse")
+                .about("Rehearse a deck, and report the time spent on each slide")
+                .arg(Arg::with_name("project-name").required(true))
+                .arg(
+                    Arg::with_name("start")
+                        .long("start")
+                        .takes_value(true)
+                        .help("Start the rehearsal at the given slide"),
+                )
+                .arg(
+                    Arg::with_name("section")
+                        .long("section")
+                        .takes_value(true)
+                        .help("Rehearse the slides of one section only"),
+                )
+                .arg(
+                    Arg::with_name("only-tags")
+                        .long("only-tags")
+                        .takes_value(true)
+                        .help("Rehearse only the slides with one of the tags, e.g. --only-tags core,demo"),
+                )
+                .arg(
+                    Arg::with_name("skip-tags")
+                        .long("skip-tags")
+                        .takes_value(true)
+                        .help("Hide the slides with one of the tags, e.g. --skip-tags appendix"),
+                )
+                .arg(
+                    Arg::with_name("slides")
+                        .long("slides")
+                        .takes_value(true)
+                        .help("Rehearse only the given slides, e.g. --slides 3-9,12"),
+                ),
+        )
+        .subcommand(
+            // Follows a presentation shared with `dough present --share`.
+            SubCommand::with_name("follow")
+                .about("Follow a shared presentation")
+                .arg(
+                    Arg::with_name("address")
+                        .default_value(DEFAULT_ADDRESS)
+                        .help("The address the presentation is shared on, a TCP address or a unix socket"),
                 ),
         )
         .get_matches();
@@ -65,7 +181,18 @@ fn main() {
         create_project(args, &mut log);
     } else if let Some(args) = matches.subcommand_matches("present") {
         // Present a project
-        present_project(args, &mut log);
+        present_project(args, &mut log, false);
+    } else if let Some(args) = matches.subcommand_matches("rehearse") {
+        // Rehearse a project, which is presented in terminal mode
+        present_project(args, &mut log, true);
+    } else if let Some(args) = matches.subcommand_matches("follow") {
+        // Follow a shared presentation
+        let address = args.value_of("address").unwrap_or(DEFAULT_ADDRESS);
+        log.info(format!("Following the presentation on {}", address));
+        if let Err(err) = follow(address) {
+            log.error(format!("Could not follow the presentation, error: {}", err));
+            process::exit(4);
+        }
     } else {
         // Print help information if no valid subcommand is provided.
         print!(
@@ -115,13 +242,15 @@ fn create_project(args: &clap::ArgMatches, log: &mut Logger) {
 /// Present a project.
 /// This function presents a project using the provided arguments.
 /// It initializes the project, and then presents it in the specified mode.
+/// A rehearsal is presented in terminal mode, and reports the time of each slide at the end.
 /// If any of these steps fail, the function prints an error message and exits with a
 /// non-zero exit code.
 /// # Arguments
 /// * `args` - The command-line arguments provided by the user.
 /// * `log` - The paris logger instance.
+/// * `rehearse` - Whether the presentation is a rehearsal.
 
-fn present_project(args: &clap::ArgMatches, log: &mut Logger) {
+fn present_project(args: &clap::ArgMatches, log: &mut Logger, rehearse: bool) {
     // Get the project name from the command-line arguments.
     let project_name = args
         .value_of("project-name")
@@ -130,19 +259,57 @@ fn present_project(args: &clap::ArgMatches, log: &mut Logger) {
     // Get the presentation mode from the command-line arguments. If no mode is provided, use terminal mode.
     let mode = args.value_of("mode").unwrap_or("term"); // Default to terminal mode
 
-    log.info(format!(
-        "Presenting project '{}' in '{}' mode",
-        project_name, mode
-    ));
+    if rehearse {
+        log.info(format!("Rehearsing project '{}'", project_name));
+    } else {
+        log.info(format!(
+            "Presenting project '{}' in '{}' mode",
+            project_name, mode
+        ));
+    }
     let cwd = env::current_dir().expect("Failed to get current working directory");
     // Create a new project instance.
     let project = Project::new(project_name, &cwd.to_str().unwrap(), "default");
+    let options = PresentOptions {
+        section: args.value_of("section").map(|section| section.to_string()),
+        start: args.value_of("start").map(|start| {
+            start.parse::<u32>().unwrap_or_else(|_| {
+                log.error(format!("Invalid slide number '{}'", start));
+                process::exit(1);
+            })
+        }),
+        rehearse,
+        auto: args.value_of("auto").map(|auto| {
+            parse_duration(auto).unwrap_or_else(|| {
+                log.error(format!("Invalid duration '{}', e.g. 15s or 1m", auto));
+                process::exit(1);
+            })
+        }),
+        wrap: args.is_present("loop"),
+        kiosk: args.is_present("kiosk"),
+        resume: args.is_present("resume"),
+        only_tags: args.value_of("only-tags").map(|tags| tags.to_string()),
+        skip_tags: args.value_of("skip-tags").map(|tags| tags.to_string()),
+        slides: args.value_of("slides").map(|slides| slides.to_string()),
+        // A bare --share shares on the default address.
+        share: args.is_present("share").then(|| {
+            args.value_of("share")
+                .unwrap_or(DEFAULT_ADDRESS)
+                .to_string()
+        }),
+        // A bare --remote serves on the default address, use 0.0.0.0:7374 to be reached on the local network.
+        remote: args.is_present("remote").then(|| {
+            args.value_of("remote")
+                .unwrap_or(DEFAULT_REMOTE_ADDRESS)
+                .to_string()
+        }),
+    };
 
     match mode {
         // The HTML mode is not implemented yet, so we only support terminal mode for now. This
         // will be updated in the future.
         "term" | _ => {
-            if let Err(err) = project.present_term() {
+            if let Err(err) = project.present_term(&options) {
                 log.error(format!(
                     "Could not present project in terminal, error: {}",
                     err
@@ -0,0 +1,250 @@
+//! Manifest reads the deck wide settings of a project from `deck.yml`, and the front matter of a slide.
+//! Both are written in the same small subset of YAML as `style.yml`:
+//! `key: value` pairs, inline lists `key: [a, b]`, and one level of nested `- item` lists or `key: value` maps.
+//!
+//! The manifest can set the order of the slides, group them in sections, and set the deck title,
+//! author, theme and code runtimes:
+//!
+//! ```yaml
+//! title: Rust for Beginners
+//! author: Ana
+//! theme: light
+//! slides:
+//!   - intro.md
+//!   - Basics: variables.md, ownership.md
+//! runtimes:
+//!   python: python3.12
+//! ```
+
+use std::collections::{BTreeMap, HashMap};
+use std::error::Error;
+use std::fs;
+use std::path::Path;
+
+/// The file name of the project manifest.
+pub const MANIFEST_FILE: &str = "deck.yml";
+
+/// A value of the manifest or the front matter.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Value {
+    Scalar(String),
+    List(Vec<String>),
+    Map(BTreeMap<String, String>),
+}
+
+/// The settings of a manifest or a front matter block.
+pub type Settings = HashMap<String, Value>;
+
+/// Read the manifest of a project.
+/// A project without a manifest has no settings.
+/// # Arguments
+/// * `dir` - The project directory.
+pub fn load(dir: &Path) -> Result<Settings, Box<dyn Error>> {
+    let path = dir.join(MANIFEST_FILE);
+    if !path.exists() {
+        return Ok(Settings::new());
+    }
+    let contents = fs::read_to_string(&path)?;
+    Ok(parse(&contents))
+}
+
+/// Parse a block of settings.
+/// Lines that do not fit the supported subset are ignored.
+pub fn parse(text: &str) -> Settings {
+    let mut settings = Settings::new();
+    // The key of the last top level entry without a value, which nested lines belong to.
+    let mut parent: Option<String> = None;
+
+    for line in text.lines() {
+        let trimmed = line.trim();
+        if trimmed.is_empty() || trimmed.starts_with('#') {
+            continue;
+        }
+        let nested = line.starts_with(' ') || line.starts_with('\t') || trimmed.starts_with("- ");
+
+        if nested {
+            let key = match &parent {
+                Some(key) => key.clone(),
+                None => continue,
+            };
+            if let Some(item) = trimmed.strip_prefix("- ") {
+                let entry = settings.entry(key).or_insert(Value::List(Vec::new()));
+                if let Value::List(items) = entry {
+                    items.push(unquote(item));
+                }
+            } else if let Some((name, value)) = trimmed.split_once(':') {
+                let entry = settings.entry(key).or_insert(Value::Map(BTreeMap::new()));
+                if let Value::Map(map) = entry {
+                    map.insert(name.trim().to_string(), unquote(value));
+                }
+            }
+            continue;
+        }
+
+        let (key, value) = match trimmed.split_once(':') {
+            Some((key, value)) => (key.trim().to_string(), value.trim()),
+            None => continue,
+        };
+        if value.is_empty() {
+            parent = Some(key);
+        } else if value.starts_with('[') && value.ends_with(']') {
+            let items = value[1..value.len() - 1]
+                .split(',')
+                .map(unquote)
+                .filter(|item| !item.is_empty())
+                .collect();
+            settings.insert(key, Value::List(items));
+            parent = None;
+        } else {
+            settings.insert(key, Value::Scalar(unquote(value)));
+            parent = None;
+        }
+    }
+
+    settings
+}
+
+/// Split the front matter off the top of a slide.
+/// The front matter is a block of settings between two `---` lines at the very start of the file.
+/// # Returns
+/// The settings of the front matter, and the markdown that follows it.
+pub fn split_front_matter(text: &str) -> (Settings, String) {
+    let mut lines = text.lines();
+    if lines.next().map(|line| line.trim_end()) != Some("---") {
+        return (Settings::new(), text.to_string());
+    }
+
+    let mut front_matter: Vec<&str> = Vec::new();
+    for line in lines.by_ref() {
+        if line.trim_end() == "---" {
+            // A block without a single setting, like a heading between two breaks, is not front matter.
+            if !front_matter.iter().copied().any(is_setting) {
+                break;
+            }
+            let rest = lines.collect::<Vec<&str>>().join("\n");
+            return (parse(&front_matter.join("\n")), rest);
+        }
+        // A line that is not a setting means the `---` was a thematic break.
+        if !line.trim().is_empty()
+            && !line.contains(':')
+            && !line.trim_start().starts_with('-')
+            && !line.trim_start().starts_with('#')
+        {
+            break;
+        }
+        front_matter.push(line);
+    }
+
+    (Settings::new(), text.to_string())
+}
+
+/// Check whether a line sets a key, e.g. `title: Rust` or `tags:` before a list.
+fn is_setting(line: &str) -> bool {
+    match line.split_once(':') {
+        Some((key, _)) => {
+            !key.is_empty()
+                && key
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-')
+        }
+        None => false,
+    }
+}
+
+/// Get a scalar setting.
+pub fn scalar<'a>(settings: &'a Settings, key: &str) -> Option<&'a str> {
+    match settings.get(key) {
+        Some(Value::Scalar(value)) => Some(value.as_str()),
+        _ => None,
+    }
+}
+
+/// Get a list setting.
+pub fn list<'a>(settings: &'a Settings, key: &str) -> Option<&'a Vec<String>> {
+    match settings.get(key) {
+        Some(Value::List(items)) => Some(items),
+        _ => None,
+    }
+}
+
+/// Get a map setting.
+pub fn map<'a>(settings: &'a Settings, key: &str) -> Option<&'a BTreeMap<String, String>> {
+    match settings.get(key) {
+        Some(Value::Map(map)) => Some(map),
+        _ => None,
+    }
+}
+
+/// Remove the surrounding quotes of a value.
+fn unquote(value: &str) -> String {
+    let value = value.trim();
+    for quote in ['"', '\''] {
+        if value.len() >= 2 && value.starts_with(quote) && value.ends_with(quote) {
+            return value[1..value.len() - 1].to_string();
+        }
+    }
+    value.to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn splits_the_front_matter_off_a_slide() {
+        let (front_matter, markdown) =
+            split_front_matter("---\nevent: RustConf\ntags: [core, demo]\n---\n# Title");
+        assert_eq!(scalar(&front_matter, "event"), Some("RustConf"));
+        assert_eq!(
+            list(&front_matter, "tags"),
+            Some(&vec!["core".to_string(), "demo".to_string()])
+        );
+        assert_eq!(markdown, "# Title");
+    }
+
+    #[test]
+    fn keeps_a_slide_without_front_matter() {
+        let text = "# Title\n\n---\n\nkey: value";
+        let (front_matter, markdown) = split_front_matter(text);
+        assert!(front_matter.is_empty());
+        assert_eq!(markdown, text);
+    }
+
+    #[test]
+    fn keeps_a_heading_between_two_breaks() {
+        let text = "---\n# Heading\n---\ntext";
+        let (front_matter, markdown) = split_front_matter(text);
+        assert!(front_matter.is_empty());
+        assert_eq!(markdown, text);
+    }
+
+    #[test]
+    fn keeps_a_break_before_text() {
+        let text = "---\nJust some text\n---";
+        assert!(split_front_matter(text).0.is_empty());
+    }
+
+    #[test]
+    fn keeps_an_unclosed_front_matter() {
+        let text = "---\ntitle: Rust\n# Title";
+        let (front_matter, markdown) = split_front_matter(text);
+        assert!(front_matter.is_empty());
+        assert_eq!(markdown, text);
+    }
+
+    #[test]
+    fn parses_nested_lists_and_maps() {
+        let settings = parse(
+            "title: 'Rust'\nslides:\n  - intro.md\n  - end.md\nruntimes:\n  python: python3.12\n# a comment",
+        );
+        assert_eq!(scalar(&settings, "title"), Some("Rust"));
+        assert_eq!(
+            list(&settings, "slides"),
+            Some(&vec!["intro.md".to_string(), "end.md".to_string()])
+        );
+        assert_eq!(
+            map(&settings, "runtimes").and_then(|runtimes| runtimes.get("python")),
+            Some(&"python3.12".to_string())
+        );
+    }
+}
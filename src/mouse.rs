@@ -0,0 +1,69 @@
+//! Mouse turns the clicks and the wheel of the mouse into commands.
+//! A click on a link follows it, a click on a code block runs it, and a click anywhere else
+//! moves to the previous or the next slide, by the half of the screen it is on.
+
+use crate::utils::strip_ansi_codes;
+
+use termion::event::{MouseButton, MouseEvent};
+use unicode_width::UnicodeWidthStr;
+
+/// The command a mouse event stands for.
+/// # Arguments
+/// * `event` - The mouse event.
+/// * `slide` - The slide as it is shown, from the first line of the terminal.
+/// * `links` - The text of each link of the slide, as it is rendered.
+/// * `codes` - The number of each code block of the slide, with the first and the last row it is shown on, counted from 0.
+/// * `width` - The width of the terminal.
+pub fn command(
+    event: &MouseEvent,
+    slide: &str,
+    links: &[String],
+    codes: &[(usize, usize, usize)],
+    width: u16,
+) -> Option<String> {
+    let (x, y) = match event {
+        MouseEvent::Press(MouseButton::WheelUp, _, _) => return Some("up".to_string()),
+        MouseEvent::Press(MouseButton::WheelDown, _, _) => return Some("down".to_string()),
+        MouseEvent::Press(MouseButton::Right, _, _) => return Some("previous".to_string()),
+        MouseEvent::Press(MouseButton::Left, x, y) => (*x as usize, *y as usize),
+        _ => return None,
+    };
+
+    let line = slide
+        .lines()
+        .nth(y.saturating_sub(1))
+        .map(strip_ansi_codes)
+        .unwrap_or_default();
+
+    if let Some(link) = link_at(&line, x, links) {
+        return Some(format!("follow {}", link + 1));
+    }
+    if let Some(code) = code_at(y, codes) {
+        return Some(format!("run {}", code));
+    }
+
+    if x <= width as usize / 2 {
+        Some("previous".to_string())
+    } else {
+        Some("next".to_string())
+    }
+}
+
+/// The index of the link shown at a column of a line.
+fn link_at(line: &str, x: usize, links: &[String]) -> Option<usize> {
+    links.iter().position(|text| {
+        line.match_indices(text.as_str()).any(|(start, text)| {
+            // The columns are one-based, and wide characters take two columns.
+            let first = UnicodeWidthStr::width(&line[..start]) + 1;
+            (first..first + UnicodeWidthStr::width(text)).contains(&x)
+        })
+    })
+}
+
+/// The number of the code block shown on a row, the rows are one-based.
+fn code_at(y: usize, codes: &[(usize, usize, usize)]) -> Option<usize> {
+    codes
+        .iter()
+        .find(|(_, first, last)| (first + 1..=last + 1).contains(&y))
+        .map(|(code, _, _)| *code)
+}
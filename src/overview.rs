@@ -0,0 +1,228 @@
+//! Overview shows every slide of the deck as a card in a grid, to find a slide and jump to it.
+//! The cards are the rendered slides, cut to fit the card.
+
+use crate::input::{self, Wait};
+use crate::utils::strip_ansi_codes;
+
+use std::collections::HashMap;
+use std::error::Error;
+use std::io::{stdout, Write};
+
+use colored::Colorize;
+use termion::event::Key;
+use termion::raw::IntoRawMode;
+use unicode_width::UnicodeWidthChar;
+
+/// The width of a card, including its border.
+const CARD_WIDTH: usize = 32;
+/// The height of a card, including its border.
+const CARD_HEIGHT: usize = 8;
+/// The space between two cards.
+const GAP: usize = 2;
+
+/// Show the overview of the deck until a slide is picked or the overview is closed.
+/// # Arguments
+/// * `slides` - The rendered slides of the deck.
+/// * `current` - The index of the slide being presented, which is selected first.
+/// * `style_map` - The style map, the number of the selected card uses the `highlighter` color.
+/// # Returns
+/// The index of the picked slide, or `None` if the overview was closed.
+pub fn show(
+    slides: &[String],
+    current: usize,
+    style_map: &HashMap<String, String>,
+) -> Result<Option<usize>, Box<dyn Error>> {
+    if slides.is_empty() {
+        return Ok(None);
+    }
+
+    let mut stdout = stdout().into_raw_mode()?;
+    let last = slides.len() - 1;
+    let mut selected = current.min(last);
+
+    loop {
+        let (width, height) = termion::terminal_size()?;
+        let columns = ((width as usize + GAP) / (CARD_WIDTH + GAP)).max(1);
+        // The last line of the terminal is kept for the help line.
+        let rows = ((height as usize).saturating_sub(1) / CARD_HEIGHT).max(1);
+
+        write!(
+            stdout,
+            "{}",
+            draw(slides, selected, (columns, rows), width as usize, style_map)
+        )?;
+        write!(
+            stdout,
+            "{}{}",
+            termion::cursor::Goto(1, height),
+            format!(
+                " {}/{}  arrows or hjkl to move, Enter to jump, Esc to close",
+                selected + 1,
+                slides.len()
+            )
+            .dimmed()
+        )?;
+        stdout.flush()?;
+
+        let key = match input::key(None) {
+            Wait::Key(key) => key,
+            _ => return Ok(None),
+        };
+        match key {
+            Key::Left | Key::Char('h') => selected = selected.saturating_sub(1),
+            Key::Right | Key::Char('l') => selected = (selected + 1).min(last),
+            Key::Up | Key::Char('k') if selected >= columns => selected -= columns,
+            Key::Down | Key::Char('j') => {
+                // The last row can be shorter, moving down from above its end selects the last slide.
+                if selected + columns <= last {
+                    selected += columns;
+                } else if selected / columns < last / columns {
+                    selected = last;
+                }
+            }
+            Key::Char('g') => selected = 0,
+            Key::Char('G') => selected = last,
+            Key::Char('\n') => return Ok(Some(selected)),
+            Key::Esc | Key::Char('o') | Key::Char('q') | Key::Ctrl('c') => return Ok(None),
+            _ => {}
+        }
+    }
+}
+
+/// Draw the page of the grid that holds the selected card.
+/// # Arguments
+/// * `grid` - The number of columns and rows of cards that fit the terminal.
+/// * `width` - The width of the terminal, the grid is centered in it.
+fn draw(
+    slides: &[String],
+    selected: usize,
+    grid: (usize, usize),
+    width: usize,
+    style_map: &HashMap<String, String>,
+) -> String {
+    let (columns, rows) = grid;
+    let grid_width = columns * CARD_WIDTH + (columns - 1) * GAP;
+    let margin = width.saturating_sub(grid_width) / 2;
+    // The grid is paged, the page holding the selected card is shown.
+    let first_row = selected / columns / rows * rows;
+
+    let mut output = format!("{}", termion::clear::All);
+    for row in 0..rows {
+        for column in 0..columns {
+            let index = (first_row + row) * columns + column;
+            let slide = match slides.get(index) {
+                Some(slide) => slide,
+                None => break,
+            };
+            let x = margin + column * (CARD_WIDTH + GAP) + 1;
+            let y = row * CARD_HEIGHT + 1;
+            for (i, line) in card(index, slide, index == selected, style_map)
+                .iter()
+                .enumerate()
+            {
+                output.push_str(&format!(
+                    "{}{}",
+                    termion::cursor::Goto(x as u16, (y + i) as u16),
+                    line
+                ));
+            }
+        }
+    }
+
+    output
+}
+
+/// The lines of the card of a slide, with a border around the start of the slide.
+fn card(
+    index: usize,
+    slide: &str,
+    selected: bool,
+    style_map: &HashMap<String, String>,
+) -> Vec<String> {
+    let inner = CARD_WIDTH - 2;
+    let border = |text: &str| {
+        if selected {
+            text.bold().to_string()
+        } else {
+            text.dimmed().to_string()
+        }
+    };
+
+    let number = format!(" {} ", index + 1);
+    let number = if selected {
+        let highlighter = style_map
+            .get("highlighter")
+            .map(|s| s.as_str())
+            .unwrap_or("black on white");
+        let (foreground, background) = highlighter.split_once(" on ").unwrap_or(("black", "white"));
+        number.color(foreground).on_color(background).to_string()
+    } else {
+        number.bold().to_string()
+    };
+    let top_width = inner.saturating_sub(index.to_string().len() + 3);
+
+    let mut lines = vec![format!(
+        "{}{}{}",
+        border("┌─"),
+        number,
+        border(&format!("{}┐", "─".repeat(top_width)))
+    )];
+    let mut content = thumbnail(slide).into_iter();
+    for _ in 0..CARD_HEIGHT - 2 {
+        let line = content.next().unwrap_or_default();
+        lines.push(format!(
+            "{}{}{}",
+            border("│"),
+            fit(&line, inner),
+            border("│")
+        ));
+    }
+    lines.push(border(&format!("└{}┘", "─".repeat(inner))));
+
+    lines
+}
+
+/// The lines of a slide worth showing on a card: the leading blank lines are dropped,
+/// and runs of blank lines are shown as one.
+fn thumbnail(slide: &str) -> Vec<String> {
+    let mut lines: Vec<String> = Vec::new();
+    let mut blank = true;
+    for line in slide.lines() {
+        let is_blank = strip_ansi_codes(line).trim().is_empty();
+        if is_blank && blank {
+            continue;
+        }
+        blank = is_blank;
+        lines.push(line.replace('\t', "    "));
+    }
+    lines
+}
+
+/// Cut a line to a width and pad it, keeping the colors of the line.
+fn fit(line: &str, width: usize) -> String {
+    let mut result = String::new();
+    let mut used = 0;
+    let mut chars = line.chars();
+
+    while let Some(c) = chars.next() {
+        if c == '\x1b' {
+            // The escape sequence is copied whole, up to its final letter.
+            result.push(c);
+            for c in chars.by_ref() {
+                result.push(c);
+                if c.is_ascii_alphabetic() {
+                    break;
+                }
+            }
+            continue;
+        }
+        let char_width = UnicodeWidthChar::width(c).unwrap_or(0);
+        if used + char_width > width {
+            break;
+        }
+        result.push(c);
+        used += char_width;
+    }
+
+    format!("{}\x1b[0m{}", result, " ".repeat(width - used))
+}
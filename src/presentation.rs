@@ -0,0 +1,68 @@
+//! Presentation holds the navigation state of a deck while it is being presented.
+
+use std::time::Duration;
+
+/// The navigation state of a presentation.
+pub struct Presentation {
+    /// The number of the slide being presented, starting at 1.
+    pub current_slide: u32,
+    /// The step the code blocks of the current slide are rendered at.
+    pub code_step: usize,
+    /// The number of lines to be rendered, used for scrolling and highlighting.
+    pub lines: u32,
+    /// Used to determine whether to render a new slide or not. Used for scrolling.
+    pub render: bool,
+    /// Used to determine whether to highlight the lines or scroll.
+    pub highlight: bool,
+    /// The link of the slide that has the focus, moved with Tab.
+    pub link_focus: Option<usize>,
+    /// The time the slide is shown for before the presentation moves on by itself.
+    pub advance: Option<Duration>,
+    /// Whether the keys are locked, as in a kiosk, all but the unlock sequence are ignored.
+    pub kiosk: bool,
+    /// The slides jumped away from, the most recent last.
+    history: Vec<u32>,
+}
+
+impl Presentation {
+    /// Start a presentation at the given slide.
+    pub fn new(start: u32) -> Presentation {
+        Presentation {
+            current_slide: start.max(1),
+            code_step: 0,
+            lines: 1,
+            render: true,
+            highlight: true,
+            link_focus: None,
+            advance: None,
+            kiosk: false,
+            history: Vec::new(),
+        }
+    }
+
+    /// Move to a slide, which is rendered fresh.
+    pub fn go_to(&mut self, slide: u32) {
+        self.current_slide = slide.max(1);
+        self.code_step = 0;
+        // scrolling starts from the bottom
+        self.lines = 1;
+        self.render = true;
+        self.link_focus = None;
+    }
+
+    /// Jump to a slide, the current slide is kept in the history to come back to.
+    pub fn jump_to(&mut self, slide: u32) {
+        if slide == self.current_slide {
+            return;
+        }
+        self.history.push(self.current_slide);
+        self.go_to(slide);
+    }
+
+    /// Go back to the slide before the last jump.
+    pub fn back(&mut self) {
+        if let Some(slide) = self.history.pop() {
+            self.go_to(slide);
+        }
+    }
+}
@@ -1,4 +1,5 @@
 extern crate lazy_static;
+use crate::fence::FenceMeta;
 use crate::utils::{
     calculate_length_of_line, calculate_length_of_longest_line, check_if_text_is_right_aligned,
     store_colors, strip_ansi_codes,
@@ -42,6 +43,34 @@ lazy_static! {
     ///     where the key is the index of the order of appearance of the code and the value is a vetor of language and code
     static ref CODES: Mutex<BTreeMap<usize, (String, String)>> = Mutex::new(BTreeMap::new());
 
+    /// This is used to store the step the code blocks are rendered at
+    /// Steps are declared on the code fence as line ranges separated by `|`, e.g. {1-3|5|7-9}
+    static ref CODE_STEP: Mutex<usize> = Mutex::new(0);
+
+    /// This is used to store the number of steps of the code blocks in the file
+    /// It is the largest number of steps declared by any code block in the file
+    static ref CODE_STEP_COUNT: Mutex<usize> = Mutex::new(0);
+
+    /// This is used to store the pattern of the search, the matches are highlighted in the slide
+    static ref SEARCH: Mutex<Option<Regex>> = Mutex::new(None);
+
+    /// This is used to store the links in the file, in the order of their appearance
+    /// Each link is stored as its url and the text it is rendered as, which is used to find the link that is clicked
+    static ref LINKS: Mutex<Vec<(String, String)>> = Mutex::new(Vec::new());
+
+    /// This is used to store the rows each code block is rendered on, by the index of the code block
+    /// The rows are counted from the first line of the slide, they are used to find the code block that is clicked
+    static ref CODE_ROWS: Mutex<BTreeMap<usize, (usize, usize)>> = Mutex::new(BTreeMap::new());
+
+    /// This is used to find the marker of a code block on a line, the marker is a style the terminal never gets
+    /// The lines of a code block are marked while the slide is aligned, so that the rows they end up on are known
+    static ref CODE_MARKER: Regex = Regex::new(r"\x1b\[;;(\d+)m").unwrap();
+
+    /// This is used to find the styles of a line, so that the background of a diff line is set again after them
+    static ref SGR: Regex = Regex::new(r"\x1b\[([0-9;]*)m").unwrap();
+
+    /// This is used to store the link that has the focus, it is rendered with the `highlighter` style
+    static ref LINK_FOCUS: Mutex<Option<usize>> = Mutex::new(None);
 
 }
 
@@ -238,6 +267,13 @@ fn visit_md_node(node: mdast::Node, depth: usize) -> Option<String> {
             codes.insert(last_index + 1, (language.clone(), code.value.to_string()));
             drop(codes);
 
+            // The line ranges on the code fence decide which lines are emphasised in the current step
+            let fence = FenceMeta::parse(code.meta.as_deref());
+            let mut step_count = CODE_STEP_COUNT.lock().unwrap();
+            *step_count = (*step_count).max(fence.steps.len());
+            drop(step_count);
+            let step = *CODE_STEP.lock().unwrap();
+
             let syntax_theme = styles
                 .get("syntax_theme")
                 .map(|s| s.as_str())
@@ -257,26 +293,69 @@ fn visit_md_node(node: mdast::Node, depth: usize) -> Option<String> {
                 _ => false,
             };
 
-            let mut result = String::from("```\n").replace("```", "");
-            if syntax_highlighting == "true" {
-                let mut highlighted_code = syntax_highlighter(
-                    &language,
-                    code.value.to_string(),
-                    syntax_theme,
-                    include_background_color,
-                );
+            // A `diff` fence, or the `diff` flag on another language, colours the lines marked with `+` and `-`
+            // On other languages the markers are split off so that the code is highlighted in its own language
+            let is_diff = language == "diff" || fence.flag("diff");
+            let (markers, source) = if is_diff && language != "diff" {
+                split_diff_markers(&code.value)
+            } else {
+                (Vec::new(), code.value.to_string())
+            };
 
-                highlighted_code = highlighted_code
+            let mut highlighted_code = if syntax_highlighting == "true" {
+                syntax_highlighter(&language, source, syntax_theme, include_background_color)
                     .lines()
-                    .map(|line| format!("{}", line))
-                    .collect::<Vec<String>>()
-                    .join("\n");
-                result.push_str(&highlighted_code.to_string());
+                    .collect::<Vec<&str>>()
+                    .join("\n")
             } else {
                 // A tab is replaced by 4 spaces to ensure uniform indentation across different terminals and different widths
-                let escaped = code.value.replace("\t", "    ");
-                result.push_str(&&escaped.to_string());
+                source.replace('\t', "    ")
+            };
+            highlighted_code = emphasise_lines(&highlighted_code, &fence, step);
+
+            if is_diff {
+                highlighted_code = colour_diff_lines(&highlighted_code, &markers, &styles);
             }
+
+            let line_numbers = styles
+                .get("code_line_numbers")
+                .map(|s| s.as_str())
+                .unwrap_or("false")
+                == "true";
+            if line_numbers || fence.flag("numbers") {
+                // Included code is numbered from the first line taken from the file
+                let first = fence
+                    .attr("start")
+                    .and_then(|start| start.parse::<usize>().ok())
+                    .unwrap_or(1);
+                highlighted_code = add_line_numbers(&highlighted_code, first);
+            }
+
+            let mut result = String::from("```\n").replace("```", "");
+            if let Some(title) = fence.attr("title") {
+                let default_caption_color = "black on white".to_string();
+                let caption_color = styles.get("code_caption").unwrap_or(&default_caption_color);
+                let colors: Vec<&str> = caption_color.split(" on ").collect();
+                let foreground_color = colors[0];
+                let background_color = colors.get(1).copied().unwrap_or("white");
+
+                result.push_str(
+                    &format!(" {} ", title)
+                        .color(foreground_color)
+                        .on_color(background_color)
+                        .to_string(),
+                );
+                result.push('\n');
+            }
+            result.push_str(&highlighted_code);
+
+            // Every line of the code block is marked with its index, the marker is removed once the slide is aligned
+            let marker = format!("\x1b[;;{}m", last_index + 1);
+            let mut result = result
+                .lines()
+                .map(|line| format!("{}{}", marker, line))
+                .collect::<Vec<String>>()
+                .join("\n");
             result.push_str("\n```\n".replace("```", "").as_str());
             Some(result)
         }
@@ -306,15 +385,37 @@ fn visit_md_node(node: mdast::Node, depth: usize) -> Option<String> {
             let mut result = String::from("[");
             result = result.replace("[", "");
 
-            result.push_str(
-                &join_children(link.children, depth)
-                    .color(color_text)
-                    .to_string(),
-            );
+            // The focused link is highlighted, so that it can be followed with Enter
+            let mut links = LINKS.lock().unwrap();
+            let index = links.len();
+            let focused = *LINK_FOCUS.lock().unwrap() == Some(index);
+            links.push((link.url.clone(), String::new()));
+            drop(links);
+
+            let text = join_children(link.children, depth);
+            if focused {
+                let default_highlight_color = "black on white".to_string();
+                let highlight_color = styles
+                    .get("highlighter")
+                    .unwrap_or(&default_highlight_color);
+                let (foreground_color, background_color) = highlight_color
+                    .split_once(" on ")
+                    .unwrap_or(("black", "white"));
+                result.push_str(
+                    &strip_ansi_codes(&text)
+                        .color(foreground_color)
+                        .on_color(background_color)
+                        .to_string(),
+                );
+            } else {
+                result.push_str(&text.color(color_text).to_string());
+            }
 
             result.push_str(" - ");
             result.push_str(&link.url.color(color_url).to_string());
 
+            LINKS.lock().unwrap()[index].1 = strip_ansi_codes(&result);
+
             Some(result)
         }
 
@@ -626,7 +727,7 @@ pub fn align_custom(
             let line_num = lines.len() as u32 - highlight_line_num;
             if line_num < lines.len() as u32 {
                 let line = lines.get_mut(line_num as usize).unwrap();
-                *line = strip_ansi_codes(line).to_string();
+                *line = format!("{}{}", code_marker(line), strip_ansi_codes(line));
                 *line = line
                     .color(foreground_color)
                     .on_color(background_color)
@@ -742,7 +843,12 @@ pub fn align_content(
     style_map: &HashMap<String, String>,
     highlight_line_num: u32,
 ) -> String {
-    let (_width, height) = termion::terminal_size().unwrap();
+    let (_width, mut height) = termion::terminal_size().unwrap();
+
+    // The last line of the terminal is kept for the footer
+    if footer_template(style_map).is_some() {
+        height = height.saturating_sub(1);
+    }
 
     // Bounds are used for scrolling
     let mut upper_bound = prettified.lines().count() as u32;
@@ -790,6 +896,64 @@ pub fn align_content(
     return prettified;
 }
 
+/// This function is used to get the template of the footer from the style map
+/// The footer is set with the `footer` key, the `progress` key shows the slide number when no footer is set
+/// A slide without a footer returns None
+pub fn footer_template(style_map: &HashMap<String, String>) -> Option<String> {
+    let footer = style_map
+        .get("footer")
+        .map(|s| s.trim_matches(|c| c == '"' || c == '\''))
+        .unwrap_or("");
+    if !footer.is_empty() {
+        return Some(footer.to_string());
+    }
+
+    match style_map.get("progress").map(|s| s.as_str()) {
+        Some("true") => Some("[{slide}/{total}] {section}".to_string()),
+        _ => None,
+    }
+}
+
+/// This function is used to render the footer on the last line of the terminal
+/// The footer text is colored with the `footer_color` key
+/// With the `progress_bar` key, the rest of the line is a bar filled up to the current slide
+pub fn render_footer(text: &str, progress: f32, style_map: &HashMap<String, String>) -> String {
+    let (width, height) = termion::terminal_size().unwrap();
+    let width = width as usize;
+
+    let color = style_map
+        .get("footer_color")
+        .map(|s| s.as_str())
+        .unwrap_or("white");
+    let progress_bar = style_map
+        .get("progress_bar")
+        .map(|s| s.as_str())
+        .unwrap_or("false")
+        == "true";
+
+    // The text is cut to the width of the terminal
+    let mut text: String = text.trim().chars().take(width.saturating_sub(1)).collect();
+    let mut footer = format!(" {}", text).color(color).to_string();
+
+    if progress_bar {
+        if !text.is_empty() {
+            text.push(' ');
+            footer.push(' ');
+        }
+        let bar_width = width.saturating_sub(UnicodeWidthStr::width(text.as_str()) + 2);
+        let filled = ((bar_width as f32) * progress.clamp(0.0, 1.0)).round() as usize;
+        footer.push_str(&"━".repeat(filled).color(color).to_string());
+        footer.push_str(&"─".repeat(bar_width - filled).dimmed().to_string());
+    }
+
+    format!(
+        "{}{}{}",
+        termion::cursor::Goto(1, height),
+        termion::clear::CurrentLine,
+        footer
+    )
+}
+
 /// This function is used to syntax highlight the code using the syntect crate
 /// The syntax highlighting is done based on the language and theme set in the style map
 /// The syntax highlighting is done using the following syntax:
@@ -825,6 +989,272 @@ pub fn syntax_highlighter(language: &str, code_section: String, theme: String, b
     highlighted
 }
 
+/// This function is used to dim the lines of a code block that are not emphasised in the current step
+/// The emphasised lines keep their syntax highlighting, the rest lose their colors and are dimmed
+fn emphasise_lines(code: &str, fence: &FenceMeta, step: usize) -> String {
+    if fence.steps.is_empty() {
+        return code.to_string();
+    }
+
+    code.lines()
+        .enumerate()
+        .map(|(i, line)| {
+            if fence.is_emphasised(step, i + 1) {
+                line.to_string()
+            } else {
+                strip_ansi_codes(line).dimmed().to_string()
+            }
+        })
+        .collect::<Vec<String>>()
+        .join("\n")
+}
+
+/// This function is used to split the `+`, `-` and ` ` markers off the lines of a code block with the `diff` flag
+/// Lines without a marker are treated as unchanged
+fn split_diff_markers(code: &str) -> (Vec<char>, String) {
+    let mut markers = Vec::new();
+    let mut source = Vec::new();
+
+    for line in code.lines() {
+        match line.chars().next() {
+            Some(marker @ ('+' | '-' | ' ')) => {
+                markers.push(marker);
+                source.push(&line[1..]);
+            }
+            _ => {
+                markers.push(' ');
+                source.push(line);
+            }
+        }
+    }
+
+    (markers, source.join("\n"))
+}
+
+/// This function is used to colour the background of the added and removed lines of a diff
+/// The colors are set in the style map using the `diff_added` and `diff_removed` keys
+/// The markers are put back in front of the lines if they were split off before highlighting
+fn colour_diff_lines(code: &str, markers: &[char], styles: &HashMap<String, String>) -> String {
+    let added = styles
+        .get("diff_added")
+        .map(|s| s.as_str())
+        .unwrap_or("green");
+    let removed = styles
+        .get("diff_removed")
+        .map(|s| s.as_str())
+        .unwrap_or("red");
+
+    code.lines()
+        .enumerate()
+        .map(|(i, line)| {
+            let (marker, line) = match markers.get(i) {
+                Some(marker) => (*marker, format!("{}{}", marker, line)),
+                None => {
+                    let plain = strip_ansi_codes(line);
+                    let marker = match plain.chars().next() {
+                        Some('+') if !plain.starts_with("+++") => '+',
+                        Some('-') if !plain.starts_with("---") => '-',
+                        _ => ' ',
+                    };
+                    (marker, line.to_string())
+                }
+            };
+
+            let background = match marker {
+                '+' => Color::from(added).to_bg_str(),
+                '-' => Color::from(removed).to_bg_str(),
+                _ => return line,
+            };
+            format!(
+                "\x1b[{}m{}\x1b[0m",
+                background,
+                keep_background(&line, &background)
+            )
+        })
+        .collect::<Vec<String>>()
+        .join("\n")
+}
+
+/// This function is used to set the background of a diff line again after every style that resets it
+/// A dimmed line ends with a reset, and the syntax highlighter may set a background of its own
+fn keep_background(line: &str, background: &str) -> String {
+    SGR.replace_all(line, |caps: &regex::Captures| {
+        let params: Vec<&str> = caps[1].split(';').collect();
+        let mut resets = caps[1].is_empty();
+        let mut i = 0;
+        while i < params.len() {
+            match params[i] {
+                "0" | "49" => resets = true,
+                // A colour of 256 or of 24 bits takes the next 2 or 4 parameters.
+                "38" | "48" => {
+                    resets |= params[i] == "48";
+                    i += if params.get(i + 1) == Some(&"5") {
+                        2
+                    } else {
+                        4
+                    };
+                }
+                param => {
+                    resets |= matches!(param.parse::<u8>(), Ok(40..=47 | 100..=107));
+                }
+            }
+            i += 1;
+        }
+        if resets {
+            format!("{}\x1b[{}m", &caps[0], background)
+        } else {
+            caps[0].to_string()
+        }
+    })
+    .into_owned()
+}
+
+/// This function is used to add a line number gutter to a code block
+/// The gutter is enabled for all code blocks with the `code_line_numbers` key, or for one with the `numbers` flag
+fn add_line_numbers(code: &str, first: usize) -> String {
+    // The gutter is as wide as the number of the last line.
+    let last = first + code.lines().count().max(1) - 1;
+    let width = last.to_string().len();
+
+    code.lines()
+        .enumerate()
+        .map(|(i, line)| {
+            let gutter = format!("{:>width$} │ ", first + i, width = width);
+            format!("{}{}", gutter.dimmed(), line)
+        })
+        .collect::<Vec<String>>()
+        .join("\n")
+}
+
+/// This is used to set the pattern of the search, `None` ends the search
+pub fn set_search(pattern: Option<Regex>) {
+    *SEARCH.lock().unwrap() = pattern;
+}
+
+/// This function is used to highlight the matches of the search with the `highlighter` style
+/// A line with a match loses its other styles, like a highlighted line does
+fn highlight_matches(prettified: &str, style_map: &HashMap<String, String>) -> String {
+    let search = SEARCH.lock().unwrap();
+    let pattern = match search.as_ref() {
+        Some(pattern) => pattern,
+        None => return prettified.to_string(),
+    };
+
+    let highlight_color = style_map
+        .get("highlighter")
+        .map(|s| s.as_str())
+        .unwrap_or("black on white");
+    let (foreground_color, background_color) = highlight_color
+        .split_once(" on ")
+        .unwrap_or(("black", "white"));
+
+    let mut lines = Vec::new();
+    for line in prettified.lines() {
+        let plain = strip_ansi_codes(line);
+        let matches: Vec<_> = pattern
+            .find_iter(&plain)
+            .filter(|found| !found.as_str().is_empty())
+            .collect();
+        if matches.is_empty() {
+            lines.push(line.to_string());
+            continue;
+        }
+
+        let mut highlighted = code_marker(line);
+        let mut end = 0;
+        for found in matches {
+            highlighted.push_str(&plain[end..found.start()]);
+            highlighted.push_str(
+                &found
+                    .as_str()
+                    .color(foreground_color)
+                    .on_color(background_color)
+                    .to_string(),
+            );
+            end = found.end();
+        }
+        highlighted.push_str(&plain[end..]);
+        lines.push(highlighted);
+    }
+
+    lines.join("\n")
+}
+
+/// This function is used to get the marker of the code block a line belongs to, empty for a line of no code block
+/// A line that loses its styles, like a highlighted line, keeps its marker
+fn code_marker(line: &str) -> String {
+    CODE_MARKER
+        .find(line)
+        .map(|marker| marker.as_str().to_string())
+        .unwrap_or_default()
+}
+
+/// This function is used to find the rows the code blocks are rendered on and to remove their markers
+fn record_code_rows(prettified: &str) -> String {
+    let mut code_rows = CODE_ROWS.lock().unwrap();
+    code_rows.clear();
+
+    for (row, line) in prettified.lines().enumerate() {
+        for captures in CODE_MARKER.captures_iter(line) {
+            if let Ok(index) = captures[1].parse::<usize>() {
+                let rows = code_rows.entry(index).or_insert((row, row));
+                rows.1 = row;
+            }
+        }
+    }
+
+    CODE_MARKER.replace_all(prettified, "").to_string()
+}
+
+/// This is used to get the rows the code blocks of the last prettified file are rendered on
+/// Each code block is returned as its index and its first and last row, the rows are counted from 0
+pub fn get_code_rows() -> Vec<(usize, usize, usize)> {
+    CODE_ROWS
+        .lock()
+        .unwrap()
+        .iter()
+        .map(|(index, (first, last))| (*index, *first, *last))
+        .collect()
+}
+
+/// This is used to set the link that has the focus, `None` for no focus
+pub fn set_link_focus(focus: Option<usize>) {
+    *LINK_FOCUS.lock().unwrap() = focus;
+}
+
+/// This is used to get the urls of the links in the file, in the order of their appearance
+pub fn get_links() -> Vec<String> {
+    LINKS
+        .lock()
+        .unwrap()
+        .iter()
+        .map(|(url, _)| url.clone())
+        .collect()
+}
+
+/// This is used to get the text the links in the file are rendered as, in the order of their appearance
+pub fn get_link_texts() -> Vec<String> {
+    LINKS
+        .lock()
+        .unwrap()
+        .iter()
+        .map(|(_, text)| text.clone())
+        .collect()
+}
+
+/// This is used to set the step the code blocks are rendered at
+/// The step is reset by the caller when moving to a new slide
+pub fn set_code_step(step: usize) {
+    let mut code_step = CODE_STEP.lock().unwrap();
+    *code_step = step;
+}
+
+/// This is used to get the number of steps of the code blocks in the last prettified file
+/// A slide without stepped code blocks has no steps
+pub fn get_code_steps() -> usize {
+    *CODE_STEP_COUNT.lock().unwrap()
+}
+
 /// This is used to get the upper and lower bounds of the content
 /// The upper and lower bounds are used for vertical alignment
 /// The upper bound is the number of blank lines at the beginning of the content
@@ -884,6 +1314,12 @@ pub fn prettify(
     *codes = BTreeMap::new();
     drop(codes);
 
+    let mut step_count = CODE_STEP_COUNT.lock().unwrap();
+    *step_count = 0;
+    drop(step_count);
+
+    LINKS.lock().unwrap().clear();
+
     let mut lines = md_text.lines();
     // let mut front_matter = Vec::new();
 
@@ -919,5 +1355,8 @@ pub fn prettify(
         prettified.pop();
     }
 
-    return Ok(align_content(prettified, style_map, highlight_line_num));
+    prettified = highlight_matches(&prettified, style_map);
+
+    let aligned = align_content(prettified, style_map, highlight_line_num);
+    return Ok(record_code_rows(&aligned));
 }
@@ -0,0 +1,146 @@
+//! Rehearsal records how long each slide of a deck is shown, while the deck is presented with `dough rehearse`.
+//! A slide with code steps is timed step by step, as each step is a fragment of the slide.
+//! The time spent on the overview, the command line prompts and the blank screens is left out.
+//! At the end of the rehearsal the times are written to `rehearsal.md` as a table, and to `rehearsal.json`,
+//! in the project directory. The time of a slide is written the way the `time` of its front matter is,
+//! so that it can be copied over as the plan of the talk.
+
+use crate::utils::{escape_json, format_duration};
+
+use std::error::Error;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+/// The time a slide was shown for.
+struct SlideTime {
+    /// The title of the slide, its first heading.
+    title: Option<String>,
+    /// The time of each step of the slide.
+    steps: Vec<Duration>,
+}
+
+impl SlideTime {
+    fn total(&self) -> Duration {
+        self.steps.iter().sum()
+    }
+}
+
+/// The times of a rehearsal.
+pub struct Rehearsal {
+    slides: Vec<SlideTime>,
+}
+
+impl Rehearsal {
+    /// Start a rehearsal.
+    pub fn new() -> Rehearsal {
+        Rehearsal { slides: Vec::new() }
+    }
+
+    /// Record the time a step of a slide was shown for, a step shown twice adds up its times.
+    /// # Arguments
+    /// * `slide` - The index of the slide.
+    /// * `step` - The code step of the slide.
+    /// * `title` - The title of the slide.
+    /// * `time` - The time the step was shown for.
+    pub fn record(&mut self, slide: usize, step: usize, title: Option<String>, time: Duration) {
+        if self.slides.len() <= slide {
+            self.slides.resize_with(slide + 1, || SlideTime {
+                title: None,
+                steps: Vec::new(),
+            });
+        }
+        let slide = &mut self.slides[slide];
+        slide.title = title;
+        if slide.steps.len() <= step {
+            slide.steps.resize(step + 1, Duration::ZERO);
+        }
+        slide.steps[step] += time;
+    }
+
+    /// The time of the whole rehearsal.
+    fn total(&self) -> Duration {
+        self.slides.iter().map(SlideTime::total).sum()
+    }
+
+    /// End the rehearsal, and write its report to the project directory.
+    /// # Returns
+    /// The report as a table, and the paths of the files it was written to.
+    pub fn finish(&self, dir: &Path) -> Result<(String, Vec<PathBuf>), Box<dyn Error>> {
+        let table = self.table();
+        let table_path = dir.join("rehearsal.md");
+        let json_path = dir.join("rehearsal.json");
+        fs::write(&table_path, format!("{}\n", table))?;
+        fs::write(&json_path, self.json())?;
+        Ok((table, vec![table_path, json_path]))
+    }
+
+    /// The report as a markdown table, with a line for every slide and for every step of a slide with steps.
+    fn table(&self) -> String {
+        let mut lines = vec![
+            "| Slide | Title | Time |".to_string(),
+            "| --- | --- | --- |".to_string(),
+        ];
+        for (index, slide) in self.slides.iter().enumerate() {
+            if slide.steps.is_empty() {
+                continue;
+            }
+            lines.push(format!(
+                "| {} | {} | {} |",
+                index + 1,
+                slide.title.as_deref().unwrap_or("").replace('|', "\\|"),
+                format_duration(slide.total())
+            ));
+            if slide.steps.len() > 1 {
+                for (step, time) in slide.steps.iter().enumerate() {
+                    lines.push(format!(
+                        "| {}.{} | step {} | {} |",
+                        index + 1,
+                        step + 1,
+                        step + 1,
+                        format_duration(*time)
+                    ));
+                }
+            }
+        }
+        lines.push(format!(
+            "| | **Total** | **{}** |",
+            format_duration(self.total())
+        ));
+        lines.join("\n")
+    }
+
+    /// The report as JSON, the times are in seconds.
+    fn json(&self) -> String {
+        let slides: Vec<String> = self
+            .slides
+            .iter()
+            .enumerate()
+            .filter(|(_, slide)| !slide.steps.is_empty())
+            .map(|(index, slide)| {
+                let title = match &slide.title {
+                    Some(title) => format!("\"{}\"", escape_json(title)),
+                    None => "null".to_string(),
+                };
+                let steps: Vec<String> = slide
+                    .steps
+                    .iter()
+                    .map(|time| format!("{:.1}", time.as_secs_f64()))
+                    .collect();
+                format!(
+                    "    {{ \"slide\": {}, \"title\": {}, \"seconds\": {:.1}, \"time\": \"{}\", \"steps\": [{}] }}",
+                    index + 1,
+                    title,
+                    slide.total().as_secs_f64(),
+                    format_duration(slide.total()),
+                    steps.join(", ")
+                )
+            })
+            .collect();
+        format!(
+            "{{\n  \"seconds\": {:.1},\n  \"slides\": [\n{}\n  ]\n}}\n",
+            self.total().as_secs_f64(),
+            slides.join(",\n")
+        )
+    }
+}
@@ -0,0 +1,574 @@
+//! Remote controls a presentation over HTTP, from the browser of a phone, a clicker app or a script.
+//! `dough present --remote` serves on 127.0.0.1:7374, and `--remote 0.0.0.0:7374` on the local network.
+//!
+//! * `GET /` - A remote page, with the next and the previous buttons, the speaker notes and the time of the talk.
+//! * `GET /state` - The slide being presented and the time of the talk, as JSON.
+//! * `POST /<command>` - Run a command, its arguments are the next parts of the path, e.g. `POST /goto/7` or `POST /run/2`.
+//!
+//! The commands that move through the talk can be sent: next, previous, goto, first, last, run, blackout,
+//! whiteout and pause. They are run as if they were typed at the `:` command line, the other commands are refused.
+//! Commands sent while a prompt, the overview or a blank screen is open wait until it is closed.
+//!
+//! On an address other than a loopback one, every request needs the token printed when the presentation starts,
+//! as `?token=` or in the `X-Dough-Token` header. On a loopback address a request must name a local host,
+//! so that a page of another site that resolves to this machine can not reach the remote.
+//! A request from a page of another site is refused, so that a page open in a browser can not drive the presentation.
+
+use crate::commands;
+use crate::input;
+use crate::timer::Timer;
+use crate::utils::escape_json;
+
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::net::{TcpListener, TcpStream};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+
+/// The address the remote is served on by default.
+pub const DEFAULT_ADDRESS: &str = "127.0.0.1:7374";
+
+/// The remote page.
+const PAGE: &str = include_str!("remote.html");
+
+/// The time a client is given to send its request.
+const READ_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// The largest body of a request that is read, the commands take no body.
+const MAX_BODY: u64 = 64 * 1024;
+
+/// The largest request line and headers that are read.
+const MAX_HEADERS: u64 = 16 * 1024;
+
+/// The number of requests answered at once, the connections past it are closed.
+const MAX_CONNECTIONS: usize = 16;
+
+/// The commands the remote can send, the others, like quit, export or unlock, are kept to the presenter.
+const COMMANDS: &[&str] = &[
+    "next", "previous", "goto", "first", "last", "run", "blackout", "whiteout", "pause",
+];
+
+/// The slide being presented, as it is reported by `GET /state`.
+pub struct Status {
+    /// The number of the slide in the deck, the number `goto` takes, and the number of slides of the deck.
+    pub slide: u32,
+    pub total: u32,
+    /// The position of the slide among the slides presented, `None` for a hidden slide, and the number of slides presented.
+    pub position: Option<u32>,
+    pub presented: u32,
+    /// The title of the slide, its first heading.
+    pub title: Option<String>,
+    /// The title of the deck.
+    pub deck: Option<String>,
+    pub section: Option<String>,
+    /// The speaker notes of the slide.
+    pub notes: Vec<String>,
+    pub kiosk: bool,
+    /// The time of the talk, read when the state is asked for.
+    pub timer: Timer,
+}
+
+impl Status {
+    /// The state as JSON, the times are in seconds.
+    fn json(&self) -> String {
+        let text = |value: &Option<String>| match value {
+            Some(value) => format!("\"{}\"", escape_json(value)),
+            None => "null".to_string(),
+        };
+        let number = |value: Option<i64>| match value {
+            Some(value) => value.to_string(),
+            None => "null".to_string(),
+        };
+        let notes: Vec<String> = self
+            .notes
+            .iter()
+            .map(|note| format!("\"{}\"", escape_json(note)))
+            .collect();
+        format!(
+            "{{\"slide\": {}, \"total\": {}, \"position\": {}, \"presented\": {}, \"title\": {}, \"deck\": {}, \"section\": {}, \"notes\": [{}], \"kiosk\": {}, \"timer\": {{\"elapsed\": {}, \"slide_time\": {}, \"remaining\": {}, \"planned\": {}, \"pace\": \"{}\"}}}}\n",
+            self.slide,
+            self.total,
+            number(self.position.map(i64::from)),
+            self.presented,
+            text(&self.title),
+            text(&self.deck),
+            text(&self.section),
+            notes.join(", "),
+            self.kiosk,
+            self.timer.elapsed().as_secs(),
+            self.timer.slide_elapsed().as_secs(),
+            number(self.timer.remaining()),
+            self.timer.is_planned(),
+            self.timer.pace().name()
+        )
+    }
+}
+
+/// A request to the remote.
+struct Request {
+    method: String,
+    path: String,
+    /// The `Host` and the `Origin` headers, to tell a request of the remote page from a request of another site.
+    host: Option<String>,
+    origin: Option<String>,
+    /// The `X-Dough-Token` header.
+    token: Option<String>,
+}
+
+/// The remote of a presentation.
+pub struct Remote {
+    /// The slide being presented, `None` until the first slide is shown.
+    status: Arc<Mutex<Option<Status>>>,
+    /// The token every request needs, `None` on a loopback address.
+    token: Option<String>,
+}
+
+impl Remote {
+    /// Serve the remote on an address, the requests are handled in threads of their own.
+    /// An address other than a loopback one is given a random token.
+    pub fn start(address: &str) -> io::Result<Remote> {
+        let listener = TcpListener::bind(address)?;
+        let token = if listener.local_addr()?.ip().is_loopback() {
+            None
+        } else {
+            Some(random_token()?)
+        };
+        let remote = Remote {
+            status: Arc::new(Mutex::new(None)),
+            token,
+        };
+        let status = Arc::clone(&remote.status);
+        let token = remote.token.clone();
+        let open = Arc::new(AtomicUsize::new(0));
+        thread::spawn(move || {
+            for stream in listener.incoming().flatten() {
+                if open.fetch_add(1, Ordering::SeqCst) >= MAX_CONNECTIONS {
+                    open.fetch_sub(1, Ordering::SeqCst);
+                    continue;
+                }
+                let status = Arc::clone(&status);
+                let token = token.clone();
+                let open = Arc::clone(&open);
+                thread::spawn(move || {
+                    let _ = handle(stream, &status, token.as_deref());
+                    open.fetch_sub(1, Ordering::SeqCst);
+                });
+            }
+        });
+        Ok(remote)
+    }
+
+    /// The address of the remote page, with the token when there is one.
+    pub fn url(&self, address: &str) -> String {
+        match &self.token {
+            Some(token) => format!("http://{}/?token={}", address, token),
+            None => format!("http://{}", address),
+        }
+    }
+
+    /// Set the slide being presented.
+    pub fn update(&self, status: Status) {
+        *self.status.lock().unwrap() = Some(status);
+    }
+}
+
+/// Answer a request to the remote.
+fn handle(
+    mut stream: TcpStream,
+    status: &Mutex<Option<Status>>,
+    token: Option<&str>,
+) -> io::Result<()> {
+    stream.set_read_timeout(Some(READ_TIMEOUT))?;
+    let request = match read_request(&stream)? {
+        Some(request) => request,
+        None => return respond(&mut stream, "400 Bad Request", &error("Bad request")),
+    };
+    let (path, query) = request
+        .path
+        .split_once('?')
+        .unwrap_or((request.path.as_str(), ""));
+
+    match token {
+        Some(token) => {
+            let sent = query
+                .split('&')
+                .find_map(|pair| pair.strip_prefix("token="))
+                .map(decode)
+                .or(request.token.clone());
+            if !sent.is_some_and(|sent| same_token(&sent, token)) {
+                return respond(
+                    &mut stream,
+                    "401 Unauthorized",
+                    &error("Open the address with the token printed when the presentation started"),
+                );
+            }
+        }
+        None if !local_host(&request) => {
+            return respond(
+                &mut stream,
+                "403 Forbidden",
+                &error("The remote only answers requests to a local host"),
+            );
+        }
+        None => {}
+    }
+
+    match (request.method.as_str(), path) {
+        ("GET", "/") => respond_with(&mut stream, "200 OK", "text/html; charset=utf-8", PAGE),
+        ("GET", "/state") => match &*status.lock().unwrap() {
+            Some(status) => respond(&mut stream, "200 OK", &status.json()),
+            None => respond(
+                &mut stream,
+                "503 Service Unavailable",
+                &error("The presentation has not started"),
+            ),
+        },
+        (_, "/" | "/state") => respond(
+            &mut stream,
+            "405 Method Not Allowed",
+            &error("Use GET to read the state, and POST to send a command"),
+        ),
+        ("POST", _) => {
+            if !same_origin(&request) {
+                return respond(
+                    &mut stream,
+                    "403 Forbidden",
+                    &error("Requests from other sites are not allowed"),
+                );
+            }
+            // The parts of the path are the command and its arguments, e.g. `/goto/7` is `goto 7`.
+            let command = path
+                .split('/')
+                .filter(|part| !part.is_empty())
+                .map(decode)
+                .collect::<Vec<String>>()
+                .join(" ");
+            let name = command.split(' ').next().unwrap_or("");
+            if let Ok(found) = commands::find(name) {
+                if !COMMANDS.contains(&found.name) {
+                    return respond(
+                        &mut stream,
+                        "403 Forbidden",
+                        &error(&format!(
+                            "The command '{}' can not be sent from the remote",
+                            found.name
+                        )),
+                    );
+                }
+            }
+            // The command is checked here, so that a mistake is answered instead of shown on the slide.
+            match commands::parse(&command) {
+                Ok(_) => {
+                    input::send(command.clone());
+                    respond(
+                        &mut stream,
+                        "200 OK",
+                        &format!("{{\"command\": \"{}\"}}\n", escape_json(&command)),
+                    )
+                }
+                Err(message) => respond(&mut stream, "400 Bad Request", &error(&message)),
+            }
+        }
+        _ => respond(&mut stream, "404 Not Found", &error("Not found")),
+    }
+}
+
+/// Read the request line and the headers of a request, the body is skipped.
+/// # Returns
+/// The request, or `None` if it is not an HTTP request.
+fn read_request(stream: &TcpStream) -> io::Result<Option<Request>> {
+    let mut reader = BufReader::new(stream.take(MAX_HEADERS + MAX_BODY));
+    let mut line = String::new();
+    reader.read_line(&mut line)?;
+    let mut parts = line.split_whitespace();
+    let (method, path) = match (parts.next(), parts.next()) {
+        (Some(method), Some(path)) => (method.to_string(), path.to_string()),
+        _ => return Ok(None),
+    };
+
+    let mut request = Request {
+        method,
+        path,
+        host: None,
+        origin: None,
+        token: None,
+    };
+    let mut length = 0;
+    loop {
+        line.clear();
+        if reader.read_line(&mut line)? == 0 || line.trim().is_empty() {
+            break;
+        }
+        if let Some((name, value)) = line.split_once(':') {
+            let value = value.trim().to_string();
+            match name.trim().to_ascii_lowercase().as_str() {
+                "host" => request.host = Some(value),
+                "origin" => request.origin = Some(value),
+                "x-dough-token" => request.token = Some(value),
+                "content-length" => length = value.parse().unwrap_or(0),
+                _ => {}
+            }
+        }
+    }
+    io::copy(&mut reader.take(length.min(MAX_BODY)), &mut io::sink())?;
+    Ok(Some(request))
+}
+
+/// Check whether a request comes from the remote page, or from a client that is not a browser.
+/// A browser sends the site of the page with every `POST`, a client like curl sends none.
+fn same_origin(request: &Request) -> bool {
+    match &request.origin {
+        Some(origin) => {
+            let site = origin
+                .split_once("://")
+                .map_or(origin.as_str(), |(_, site)| site);
+            request.host.as_deref() == Some(site)
+        }
+        None => true,
+    }
+}
+
+/// Check whether a request names a local host, like `localhost:7374` or `127.0.0.1:7374`.
+/// A page of another site that resolves its name to this machine names its own host, and is refused.
+fn local_host(request: &Request) -> bool {
+    let host = match &request.host {
+        Some(host) => host.as_str(),
+        None => return true,
+    };
+    // The port is cut off, an IPv6 address is in brackets.
+    let name = match host.rsplit_once(':') {
+        Some((name, port)) if !port.contains(']') => name,
+        _ => host,
+    };
+    name.eq_ignore_ascii_case("localhost")
+        || name == "[::1]"
+        || name
+            .parse::<std::net::Ipv4Addr>()
+            .is_ok_and(|ip| ip.is_loopback())
+}
+
+/// Compare a token sent with the token of the remote, in a time that does not tell how much of it is right.
+fn same_token(sent: &str, token: &str) -> bool {
+    sent.len() == token.len()
+        && sent
+            .bytes()
+            .zip(token.bytes())
+            .fold(0, |difference, (a, b)| difference | (a ^ b))
+            == 0
+}
+
+/// A random token, 16 bytes of the random source of the system as hex.
+fn random_token() -> io::Result<String> {
+    let mut bytes = [0u8; 16];
+    File::open("/dev/urandom")?.read_exact(&mut bytes)?;
+    Ok(bytes.iter().map(|byte| format!("{:02x}", byte)).collect())
+}
+
+/// Decode the percent escapes of a part of a path, e.g. `%20` for a space.
+fn decode(part: &str) -> String {
+    let bytes = part.as_bytes();
+    let mut decoded = Vec::new();
+    let mut index = 0;
+    while index < bytes.len() {
+        let escape = bytes
+            .get(index + 1..index + 3)
+            .and_then(|hex| std::str::from_utf8(hex).ok())
+            .and_then(|hex| u8::from_str_radix(hex, 16).ok());
+        match (bytes[index], escape) {
+            (b'%', Some(byte)) => {
+                decoded.push(byte);
+                index += 3;
+            }
+            (byte, _) => {
+                decoded.push(byte);
+                index += 1;
+            }
+        }
+    }
+    String::from_utf8_lossy(&decoded).into_owned()
+}
+
+/// An error as JSON.
+fn error(message: &str) -> String {
+    format!("{{\"error\": \"{}\"}}\n", escape_json(message))
+}
+
+/// Send a JSON response.
+fn respond(stream: &mut TcpStream, status: &str, body: &str) -> io::Result<()> {
+    respond_with(stream, status, "application/json", body)
+}
+
+/// Send a response, the connection is closed after it.
+fn respond_with(
+    stream: &mut TcpStream,
+    status: &str,
+    content_type: &str,
+    body: &str,
+) -> io::Result<()> {
+    write!(
+        stream,
+        "HTTP/1.1 {}\r\nContent-Type: {}\r\nContent-Length: {}\r\nCache-Control: no-store\r\nConnection: close\r\n\r\n{}",
+        status,
+        content_type,
+        body.len(),
+        body
+    )?;
+    stream.flush()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Send a request to `handle` over a connection of its own, and read the response.
+    fn send(request: &str, token: Option<&str>) -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let mut client = TcpStream::connect(listener.local_addr().unwrap()).unwrap();
+        let (server, _) = listener.accept().unwrap();
+        let token = token.map(str::to_string);
+        let handler = thread::spawn(move || {
+            let status = Mutex::new(None);
+            handle(server, &status, token.as_deref()).unwrap();
+        });
+        client.write_all(request.as_bytes()).unwrap();
+        let mut response = String::new();
+        client.read_to_string(&mut response).unwrap();
+        handler.join().unwrap();
+        response
+    }
+
+    fn post(path: &str) -> String {
+        send(
+            &format!("POST {} HTTP/1.1\r\nHost: 127.0.0.1:7374\r\n\r\n", path),
+            None,
+        )
+    }
+
+    #[test]
+    fn serves_the_page() {
+        let response = send("GET / HTTP/1.1\r\nHost: localhost:7374\r\n\r\n", None);
+        assert!(response.starts_with("HTTP/1.1 200 OK"));
+        assert!(response.contains("text/html"));
+    }
+
+    #[test]
+    fn has_no_state_before_the_first_slide() {
+        let response = send("GET /state HTTP/1.1\r\nHost: 127.0.0.1\r\n\r\n", None);
+        assert!(response.starts_with("HTTP/1.1 503"));
+    }
+
+    #[test]
+    fn sends_the_navigation_commands() {
+        assert!(post("/goto/7").ends_with("{\"command\": \"goto 7\"}\n"));
+        assert!(post("/previous").starts_with("HTTP/1.1 200 OK"));
+    }
+
+    #[test]
+    fn refuses_the_other_commands() {
+        for path in ["/quit", "/export/notes.md", "/unlock", "/notes", "/q"] {
+            assert!(post(path).starts_with("HTTP/1.1 403"), "{}", path);
+        }
+    }
+
+    #[test]
+    fn answers_a_mistake() {
+        assert!(post("/goto").starts_with("HTTP/1.1 400"));
+        assert!(post("/dance").starts_with("HTTP/1.1 400"));
+    }
+
+    #[test]
+    fn refuses_another_host_on_loopback() {
+        let response = send(
+            "POST /next HTTP/1.1\r\nHost: attacker.example:7374\r\n\r\n",
+            None,
+        );
+        assert!(response.starts_with("HTTP/1.1 403"));
+    }
+
+    #[test]
+    fn refuses_another_site() {
+        let response = send(
+            "POST /next HTTP/1.1\r\nHost: 127.0.0.1:7374\r\nOrigin: http://attacker.example\r\n\r\n",
+            None,
+        );
+        assert!(response.starts_with("HTTP/1.1 403"));
+    }
+
+    #[test]
+    fn needs_the_token() {
+        let token = Some("0123abcd");
+        let request = |path: &str, header: &str| {
+            send(
+                &format!(
+                    "POST {} HTTP/1.1\r\nHost: 192.168.1.20:7374\r\n{}\r\n",
+                    path, header
+                ),
+                token,
+            )
+        };
+        assert!(request("/next", "").starts_with("HTTP/1.1 401"));
+        assert!(request("/next?token=0123abce", "").starts_with("HTTP/1.1 401"));
+        assert!(request("/next?token=0123abcd", "").starts_with("HTTP/1.1 200"));
+        assert!(request("/next", "X-Dough-Token: 0123abcd\r\n").starts_with("HTTP/1.1 200"));
+    }
+
+    fn request(host: Option<&str>, origin: Option<&str>) -> Request {
+        Request {
+            method: "POST".to_string(),
+            path: "/next".to_string(),
+            host: host.map(str::to_string),
+            origin: origin.map(str::to_string),
+            token: None,
+        }
+    }
+
+    #[test]
+    fn knows_a_local_host() {
+        for host in [
+            "localhost:7374",
+            "LOCALHOST",
+            "127.0.0.1:7374",
+            "127.1.2.3",
+            "[::1]:7374",
+        ] {
+            assert!(local_host(&request(Some(host), None)), "{}", host);
+        }
+        for host in [
+            "attacker.example:7374",
+            "192.168.1.20:7374",
+            "127.0.0.1.example",
+        ] {
+            assert!(!local_host(&request(Some(host), None)), "{}", host);
+        }
+    }
+
+    #[test]
+    fn matches_the_origin_with_the_host() {
+        assert!(same_origin(&request(Some("127.0.0.1:7374"), None)));
+        assert!(same_origin(&request(
+            Some("127.0.0.1:7374"),
+            Some("http://127.0.0.1:7374")
+        )));
+        assert!(!same_origin(&request(
+            Some("127.0.0.1:7374"),
+            Some("http://attacker.example")
+        )));
+    }
+
+    #[test]
+    fn decodes_the_percent_escapes() {
+        assert_eq!(decode("a%20b"), "a b");
+        assert_eq!(decode("%2Fslides%2f"), "/slides/");
+        assert_eq!(decode("100%"), "100%");
+        assert_eq!(decode("%zz"), "%zz");
+    }
+
+    #[test]
+    fn compares_the_tokens() {
+        assert!(same_token("abc", "abc"));
+        assert!(!same_token("abd", "abc"));
+        assert!(!same_token("ab", "abc"));
+    }
+}
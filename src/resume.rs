@@ -0,0 +1,109 @@
+//! Resume keeps the last position of a presentation, so that `dough present --resume` reopens there
+//! after a crash of the terminal or a quit by accident.
+//! The position is written on every render to the state directory of dough,
+//! `$XDG_STATE_HOME/dough`, or `~/.local/state/dough`, in a file named after the path of the project:
+//!
+//! ```yaml
+//! slide: 12
+//! step: 1
+//! lines: 4
+//! render: false
+//! highlight: true
+//! section: basics
+//! ```
+
+use crate::manifest;
+use crate::presentation::Presentation;
+
+use std::env;
+use std::error::Error;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// The position of a presentation.
+pub struct Position {
+    pub slide: u32,
+    /// The code step of the slide.
+    pub step: usize,
+    /// The scroll position, or the highlighted line.
+    pub lines: u32,
+    pub render: bool,
+    pub highlight: bool,
+    /// The section the presentation was limited to.
+    pub section: Option<String>,
+}
+
+impl Position {
+    /// Put a presentation back at the position.
+    pub fn restore(&self, presentation: &mut Presentation) {
+        presentation.go_to(self.slide);
+        presentation.code_step = self.step;
+        presentation.lines = self.lines;
+        presentation.render = self.render;
+        presentation.highlight = self.highlight;
+    }
+}
+
+/// Save the position of a presentation.
+/// # Arguments
+/// * `project` - The project being presented, a directory or a single file deck.
+/// * `section` - The section the presentation is limited to.
+/// * `presentation` - The presentation.
+pub fn save(
+    project: &Path,
+    section: Option<&str>,
+    presentation: &Presentation,
+) -> Result<(), Box<dyn Error>> {
+    let path = state_file(project)?;
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    let mut state = format!(
+        "slide: {}\nstep: {}\nlines: {}\nrender: {}\nhighlight: {}\n",
+        presentation.current_slide,
+        presentation.code_step,
+        presentation.lines,
+        presentation.render,
+        presentation.highlight
+    );
+    if let Some(section) = section {
+        state.push_str(&format!("section: {}\n", section));
+    }
+    fs::write(path, state)?;
+    Ok(())
+}
+
+/// Load the last position of a project.
+/// # Returns
+/// The position, or `None` if the project has not been presented before.
+pub fn load(project: &Path) -> Result<Option<Position>, Box<dyn Error>> {
+    let path = state_file(project)?;
+    if !path.exists() {
+        return Ok(None);
+    }
+    let state = manifest::parse(&fs::read_to_string(&path)?);
+    let scalar = |key: &str| manifest::scalar(&state, key).unwrap_or("");
+    Ok(Some(Position {
+        slide: scalar("slide").parse().unwrap_or(1),
+        step: scalar("step").parse().unwrap_or(0),
+        lines: scalar("lines").parse().unwrap_or(1),
+        render: scalar("render") != "false",
+        highlight: scalar("highlight") != "false",
+        section: manifest::scalar(&state, "section").map(|section| section.to_string()),
+    }))
+}
+
+/// The file the position of a project is kept in, named after the absolute path of the project,
+/// e.g. `%home%ana%talks%rust.yml` for `/home/ana/talks/rust`.
+fn state_file(project: &Path) -> Result<PathBuf, Box<dyn Error>> {
+    let dir = match env::var_os("XDG_STATE_HOME").filter(|dir| !dir.is_empty()) {
+        Some(dir) => PathBuf::from(dir),
+        None => match env::var_os("HOME") {
+            Some(home) => PathBuf::from(home).join(".local").join("state"),
+            None => return Err("The state directory can not be found, HOME is not set".into()),
+        },
+    };
+    let project = fs::canonicalize(project)?;
+    let name = project.to_string_lossy().replace(['/', '\\'], "%");
+    Ok(dir.join("dough").join(format!("{}.yml", name)))
+}
@@ -0,0 +1,112 @@
+//! Screen blanks the screen during a talk, to draw the attention of the audience away from the slides.
+//! The screen is black, white, or a pause screen with a message and a countdown,
+//! and the slide comes back on any key.
+
+use crate::input::{self, Wait};
+use crate::utils::format_duration;
+
+use std::collections::HashMap;
+use std::error::Error;
+use std::io::{stdout, Write};
+use std::time::{Duration, Instant};
+
+use colored::Colorize;
+use termion::color;
+use termion::raw::IntoRawMode;
+use unicode_width::UnicodeWidthStr;
+
+/// A blank screen.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Screen {
+    Black,
+    White,
+    /// A pause screen, with a countdown of the given minutes.
+    /// Without minutes, the `pause_minutes` of the style config are counted down.
+    Pause(Option<u32>),
+}
+
+/// Show a blank screen until a key is pressed.
+/// # Arguments
+/// * `screen` - The screen to show.
+/// * `style_map` - The style map, with the `pause_message` and the `pause_minutes` of the pause screen.
+pub fn show(screen: &Screen, style_map: &HashMap<String, String>) -> Result<(), Box<dyn Error>> {
+    let mut stdout = stdout().into_raw_mode()?;
+    write!(stdout, "{}", termion::cursor::Hide)?;
+
+    let minutes = match screen {
+        Screen::Pause(minutes) => minutes.or_else(|| {
+            style_map
+                .get("pause_minutes")
+                .and_then(|minutes| minutes.parse().ok())
+        }),
+        _ => None,
+    };
+    // Zero minutes show the message without a countdown.
+    let end = minutes
+        .filter(|minutes| *minutes > 0)
+        .map(|minutes| Instant::now() + Duration::from_secs(minutes as u64 * 60));
+
+    loop {
+        let background = match screen {
+            Screen::Black => color::Bg(color::Black).to_string(),
+            Screen::White => color::Bg(color::LightWhite).to_string(),
+            Screen::Pause(_) => color::Bg(color::Reset).to_string(),
+        };
+        write!(stdout, "{}{}", background, termion::clear::All)?;
+        if let Screen::Pause(_) = screen {
+            let remaining = end.map(|end| end.saturating_duration_since(Instant::now()));
+            draw_pause(&mut stdout, style_map, minutes, remaining)?;
+        }
+        stdout.flush()?;
+
+        // The countdown is drawn again every second, the other screens wait for the key only.
+        let deadline = end.map(|_| Instant::now() + Duration::from_secs(1));
+        match input::key(deadline) {
+            Wait::Timeout => continue,
+            _ => break,
+        }
+    }
+
+    write!(stdout, "{}", color::Bg(color::Reset))?;
+    stdout.flush()?;
+    Ok(())
+}
+
+/// Draw the message and the countdown of the pause screen, in the middle of the terminal.
+fn draw_pause(
+    stdout: &mut impl Write,
+    style_map: &HashMap<String, String>,
+    minutes: Option<u32>,
+    remaining: Option<Duration>,
+) -> Result<(), Box<dyn Error>> {
+    let (width, height) = termion::terminal_size()?;
+    let color = style_map.get("h1").map(|s| s.as_str()).unwrap_or("red");
+    let message = style_map
+        .get("pause_message")
+        .map(|s| s.trim_matches(|c| c == '"' || c == '\''))
+        .filter(|message| !message.is_empty())
+        .unwrap_or("We will be right back")
+        .replace("{minutes}", &minutes.unwrap_or(0).to_string());
+
+    let mut lines = vec![message.bold().color(color).to_string()];
+    let mut widths = vec![UnicodeWidthStr::width(message.as_str())];
+    if let Some(remaining) = remaining {
+        let countdown = format_duration(remaining);
+        widths.push(countdown.len());
+        lines.push(countdown.dimmed().to_string());
+    }
+
+    let top = (height as usize / 2).saturating_sub(lines.len() / 2).max(1);
+    for (i, (line, line_width)) in lines.iter().zip(widths).enumerate() {
+        let left = (width as usize).saturating_sub(line_width) / 2 + 1;
+        // The message and the countdown are set one line apart.
+        let row = top + i * 2;
+        write!(
+            stdout,
+            "{}{}",
+            termion::cursor::Goto(left as u16, row as u16),
+            line
+        )?;
+    }
+    Ok(())
+}
@@ -0,0 +1,101 @@
+//! Search finds text across the slides of a deck, including the code blocks and the speaker notes.
+//! The query is a regular expression, a query that is not a valid one is searched as plain text.
+//! A query in lower case ignores case, a query with an upper case letter matches case.
+
+use regex::Regex;
+
+/// A search across the slides of a deck.
+pub struct Search {
+    /// The query as it was typed.
+    pub query: String,
+    /// The pattern the query is matched with.
+    pub pattern: Regex,
+    /// The slide index of every match, in the order of the deck.
+    hits: Vec<usize>,
+    /// The match the presentation is at.
+    current: Option<usize>,
+}
+
+impl Search {
+    /// Search the slides for a query.
+    /// # Arguments
+    /// * `query` - The text or regular expression to search for.
+    /// * `slides` - The text of each slide of the deck.
+    pub fn new(query: &str, slides: &[String]) -> Search {
+        let pattern = pattern(query);
+        let mut hits = Vec::new();
+        for (index, slide) in slides.iter().enumerate() {
+            let matches = pattern
+                .find_iter(slide)
+                .filter(|found| !found.as_str().is_empty())
+                .count();
+            hits.extend(std::iter::repeat_n(index, matches));
+        }
+
+        Search {
+            query: query.to_string(),
+            pattern,
+            hits,
+            current: None,
+        }
+    }
+
+    /// Move to the first match on or after a slide, wrapping around to the start of the deck.
+    /// # Returns
+    /// The index of the slide of the match, or `None` if nothing matched.
+    pub fn first_from(&mut self, slide: usize) -> Option<usize> {
+        if self.hits.is_empty() {
+            return None;
+        }
+        let index = self.hits.iter().position(|&hit| hit >= slide).unwrap_or(0);
+        self.current = Some(index);
+        Some(self.hits[index])
+    }
+
+    /// Move to the next match, wrapping around to the first one.
+    pub fn next(&mut self) -> Option<usize> {
+        if self.hits.is_empty() {
+            return None;
+        }
+        let index = self
+            .current
+            .map_or(0, |current| (current + 1) % self.hits.len());
+        self.current = Some(index);
+        Some(self.hits[index])
+    }
+
+    /// Move to the previous match, wrapping around to the last one.
+    pub fn previous(&mut self) -> Option<usize> {
+        if self.hits.is_empty() {
+            return None;
+        }
+        let index = match self.current {
+            Some(0) | None => self.hits.len() - 1,
+            Some(current) => current - 1,
+        };
+        self.current = Some(index);
+        Some(self.hits[index])
+    }
+
+    /// The status of the search, e.g. `/lifetime 2/5`.
+    pub fn status(&self) -> String {
+        match self.current {
+            Some(current) if !self.hits.is_empty() => {
+                format!("/{} {}/{}", self.query, current + 1, self.hits.len())
+            }
+            _ => format!("/{} no matches", self.query),
+        }
+    }
+}
+
+/// The pattern of a query.
+/// A query without upper case letters ignores case, an invalid regular expression is matched as plain text.
+pub fn pattern(query: &str) -> Regex {
+    let flags = if query.chars().any(|c| c.is_uppercase()) {
+        ""
+    } else {
+        "(?i)"
+    };
+    Regex::new(&format!("{}{}", flags, query))
+        .unwrap_or_else(|_| Regex::new(&format!("{}{}", flags, regex::escape(query))).unwrap())
+}
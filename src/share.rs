@@ -0,0 +1,270 @@
+//! Share mirrors a presentation to other terminals.
+//! `dough present --share` publishes the slide being presented on a TCP port, or on a unix socket,
+//! and `dough follow` renders the same slide at the size of its own terminal as the presenter moves.
+//! An address with a port, like `127.0.0.1:7373`, is a TCP address, any other address is the path of a unix socket.
+//!
+//! Every state is sent whole, so a follower that joins late is shown the current slide at once.
+//! A state is a header line, `dough` followed by the numbers of the state and the sizes of its parts in bytes,
+//! then the style, the markdown and the footer of the slide. The runtimes of the style are not sent.
+//! Each follower is written to in a thread of its own, so a follower that stops reading does not hold up the presenter.
+
+use std::collections::HashMap;
+use std::fs;
+use std::io::{self, BufRead, BufReader, Write};
+use std::net::{TcpListener, TcpStream};
+use std::os::unix::fs::FileTypeExt;
+use std::os::unix::net::{UnixListener, UnixStream};
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Condvar, Mutex};
+use std::thread;
+use std::time::Duration;
+
+/// The address presentations are shared on by default.
+pub const DEFAULT_ADDRESS: &str = "127.0.0.1:7373";
+
+/// The time a follower is given to take a state, before it is dropped.
+const WRITE_TIMEOUT: Duration = Duration::from_secs(1);
+
+/// The largest part of a state that is read, a larger one is not sent by a presenter.
+const MAX_PART: usize = 4 * 1024 * 1024;
+
+/// The keys of the style that are kept to the presenter, the runtimes the code blocks are run with.
+const RUNTIME_KEYS: &[&str] = &[
+    "- runtime_map",
+    "runtime_map",
+    "python",
+    "javascript",
+    "ruby",
+    "c",
+    "cpp",
+    "java",
+    "go",
+    "rust",
+    "swift",
+    "kotlin",
+    "typescript",
+];
+
+/// The slide being presented, as it is sent to the followers.
+pub struct State {
+    /// The position of the slide among the slides presented, and their number, for the progress bar.
+    pub slide: u32,
+    pub total: u32,
+    /// The step the code blocks of the slide are rendered at.
+    pub code_step: usize,
+    /// The scroll position, or the highlighted line.
+    pub lines: u32,
+    pub highlight: bool,
+    pub render: bool,
+    /// The style of the slide, the runtimes are left out when it is sent.
+    pub style_map: HashMap<String, String>,
+    /// The markdown of the slide, with its includes, variables and table of contents expanded.
+    pub markdown: String,
+    /// The footer of the slide, as it was shown to the presenter.
+    pub footer: String,
+}
+
+impl State {
+    /// Encode the state to be sent.
+    fn encode(&self) -> Vec<u8> {
+        let style: String = self
+            .style_map
+            .iter()
+            .filter(|(key, _)| !RUNTIME_KEYS.contains(&key.as_str()))
+            .map(|(key, value)| format!("{}: {}\n", key, value.replace('\n', " ")))
+            .collect();
+        let mut message = format!(
+            "dough {} {} {} {} {} {} {} {} {}\n",
+            self.slide,
+            self.total,
+            self.code_step,
+            self.lines,
+            self.highlight,
+            self.render,
+            style.len(),
+            self.markdown.len(),
+            self.footer.len()
+        )
+        .into_bytes();
+        message.extend(style.bytes());
+        message.extend(self.markdown.bytes());
+        message.extend(self.footer.bytes());
+        message
+    }
+
+    /// Read a state sent by the presenter.
+    /// # Returns
+    /// The state, or `None` once the presenter is gone.
+    pub fn read(reader: &mut impl BufRead) -> io::Result<Option<State>> {
+        let mut header = String::new();
+        if reader.read_line(&mut header)? == 0 {
+            return Ok(None);
+        }
+        let invalid = || io::Error::new(io::ErrorKind::InvalidData, "Not a dough presentation");
+        let fields: Vec<&str> = header.split_whitespace().collect();
+        if fields.len() != 10 || fields[0] != "dough" {
+            return Err(invalid());
+        }
+        let number = |index: usize| fields[index].parse::<usize>().map_err(|_| invalid());
+
+        let mut read_text = |len: usize| -> io::Result<String> {
+            if len > MAX_PART {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    "A part of the state is too large",
+                ));
+            }
+            let mut buffer = vec![0; len];
+            reader.read_exact(&mut buffer)?;
+            String::from_utf8(buffer).map_err(|_| invalid())
+        };
+        let style = read_text(number(7)?)?;
+        let markdown = read_text(number(8)?)?;
+        let footer = read_text(number(9)?)?;
+
+        Ok(Some(State {
+            slide: number(1)? as u32,
+            total: number(2)? as u32,
+            code_step: number(3)?,
+            lines: number(4)? as u32,
+            highlight: fields[5] == "true",
+            render: fields[6] == "true",
+            style_map: style
+                .lines()
+                .filter_map(|line| line.split_once(": "))
+                .map(|(key, value)| (key.to_string(), value.to_string()))
+                .collect(),
+            markdown,
+            footer,
+        }))
+    }
+}
+
+/// A connection to a follower.
+type Follower = Box<dyn Write + Send>;
+
+/// The state waiting to be sent to a follower, a newer state replaces a state that is not sent yet.
+struct Outbox {
+    pending: Mutex<Option<Arc<Vec<u8>>>>,
+    ready: Condvar,
+    /// Set once the follower is gone.
+    gone: AtomicBool,
+}
+
+impl Outbox {
+    /// Start writing to a follower in a thread of its own, beginning with a state.
+    fn start(mut follower: Follower, state: Arc<Vec<u8>>) -> Arc<Outbox> {
+        let outbox = Arc::new(Outbox {
+            pending: Mutex::new(Some(state)),
+            ready: Condvar::new(),
+            gone: AtomicBool::new(false),
+        });
+        let writer = Arc::clone(&outbox);
+        thread::spawn(move || loop {
+            let mut pending = writer.pending.lock().unwrap();
+            while pending.is_none() {
+                pending = writer.ready.wait(pending).unwrap();
+            }
+            let message = pending.take().unwrap();
+            drop(pending);
+            if follower.write_all(&message).and(follower.flush()).is_err() {
+                writer.gone.store(true, Ordering::SeqCst);
+                break;
+            }
+        });
+        outbox
+    }
+
+    /// Hand a state to the writer of the follower.
+    fn post(&self, message: Arc<Vec<u8>>) {
+        *self.pending.lock().unwrap() = Some(message);
+        self.ready.notify_one();
+    }
+}
+
+/// The followers of a shared presentation.
+pub struct Share {
+    followers: Arc<Mutex<Vec<Arc<Outbox>>>>,
+    /// The last state sent, a follower that joins is sent it at once.
+    last: Arc<Mutex<Arc<Vec<u8>>>>,
+}
+
+impl Share {
+    /// Share a presentation on an address, the followers are accepted in a thread of their own.
+    pub fn start(address: &str) -> io::Result<Share> {
+        let share = Share {
+            followers: Arc::new(Mutex::new(Vec::new())),
+            last: Arc::new(Mutex::new(Arc::new(Vec::new()))),
+        };
+        let followers = Arc::clone(&share.followers);
+        let last = Arc::clone(&share.last);
+        let welcome = move |follower: Follower| {
+            let mut followers = followers.lock().unwrap();
+            let state = Arc::clone(&last.lock().unwrap());
+            followers.push(Outbox::start(follower, state));
+        };
+
+        if is_tcp(address) {
+            let listener = TcpListener::bind(address)?;
+            thread::spawn(move || {
+                for stream in listener.incoming().flatten() {
+                    // A follower that stops reading does not hold up the presentation.
+                    let _ = stream.set_write_timeout(Some(WRITE_TIMEOUT));
+                    welcome(Box::new(stream));
+                }
+            });
+        } else {
+            // A socket left behind by a presentation that did not exit cleanly is replaced, any other file is left alone.
+            if let Ok(metadata) = fs::symlink_metadata(address) {
+                if !metadata.file_type().is_socket() {
+                    return Err(io::Error::new(
+                        io::ErrorKind::AddrInUse,
+                        format!("'{}' is a file, not a socket", address),
+                    ));
+                }
+                if UnixStream::connect(address).is_err() {
+                    fs::remove_file(address)?;
+                }
+            }
+            let listener = UnixListener::bind(address)?;
+            thread::spawn(move || {
+                for stream in listener.incoming().flatten() {
+                    let _ = stream.set_write_timeout(Some(WRITE_TIMEOUT));
+                    welcome(Box::new(stream));
+                }
+            });
+        }
+        Ok(share)
+    }
+
+    /// Send a state to every follower, the followers that are gone are dropped.
+    /// The state is handed to the writers of the followers, the presentation does not wait for them.
+    pub fn publish(&self, state: &State) {
+        let message = Arc::new(state.encode());
+        let mut followers = self.followers.lock().unwrap();
+        followers.retain(|outbox| !outbox.gone.load(Ordering::SeqCst));
+        for outbox in followers.iter() {
+            outbox.post(Arc::clone(&message));
+        }
+        *self.last.lock().unwrap() = message;
+    }
+}
+
+/// Connect to a shared presentation.
+pub fn connect(address: &str) -> io::Result<Box<dyn BufRead + Send>> {
+    if is_tcp(address) {
+        Ok(Box::new(BufReader::new(TcpStream::connect(address)?)))
+    } else {
+        Ok(Box::new(BufReader::new(UnixStream::connect(Path::new(
+            address,
+        ))?)))
+    }
+}
+
+/// Check whether an address is a TCP address, which ends with a port.
+fn is_tcp(address: &str) -> bool {
+    address
+        .rsplit_once(':')
+        .is_some_and(|(_, port)| port.parse::<u16>().is_ok())
+}
@@ -0,0 +1,295 @@
+//! Subset presents a part of a deck, picked by the tags of the slides or by their numbers,
+//! so that the short and the long version of a talk are the same deck.
+//! The tags of a slide are set in its front matter:
+//!
+//! ```yaml
+//! tags: [deep-dive, appendix]
+//! ```
+//!
+//! The slides left out are hidden, the presentation steps over them, but they can still be reached by number.
+
+use crate::deck::Deck;
+use crate::manifest::{self, Settings};
+
+/// The slides of a deck to present.
+pub struct Filter {
+    /// Only the slides with one of these tags are presented.
+    pub only_tags: Vec<String>,
+    /// The slides with one of these tags are left out.
+    pub skip_tags: Vec<String>,
+    /// Only the slides with these numbers are presented, as ranges of slide numbers.
+    pub slides: Vec<(u32, u32)>,
+}
+
+impl Filter {
+    /// Check whether a slide is presented.
+    /// # Arguments
+    /// * `number` - The number of the slide in the deck, starting at 1.
+    /// * `front_matter` - The front matter of the slide, with its tags.
+    fn shows(&self, number: u32, front_matter: &Settings) -> bool {
+        let tags = tags(front_matter);
+        let has = |wanted: &[String]| wanted.iter().any(|tag| tags.contains(tag));
+        (self.slides.is_empty()
+            || self
+                .slides
+                .iter()
+                .any(|(first, last)| (*first..=*last).contains(&number)))
+            && (self.only_tags.is_empty() || has(&self.only_tags))
+            && !has(&self.skip_tags)
+    }
+}
+
+/// The slides of a deck that are presented.
+pub struct Subset {
+    /// The numbers of the slides presented, starting at 1.
+    shown: Vec<u32>,
+}
+
+impl Subset {
+    /// Pick the slides of a deck to present.
+    /// # Returns
+    /// The slides, or a message if the filter leaves no slide.
+    pub fn new(deck: &Deck, filter: &Filter) -> Result<Subset, String> {
+        let shown: Vec<u32> = deck
+            .slides
+            .iter()
+            .enumerate()
+            .map(|(index, slide)| (index as u32 + 1, slide))
+            .filter(|(number, slide)| filter.shows(*number, &slide.front_matter))
+            .map(|(number, _)| number)
+            .collect();
+        if shown.is_empty() && !deck.slides.is_empty() {
+            return Err("No slide matches the tags and the slide numbers to present".to_string());
+        }
+        Ok(Subset { shown })
+    }
+
+    /// The number of slides presented.
+    pub fn len(&self) -> u32 {
+        self.shown.len() as u32
+    }
+
+    /// The first slide presented.
+    pub fn first(&self) -> u32 {
+        self.shown.first().copied().unwrap_or(1)
+    }
+
+    /// The last slide presented.
+    pub fn last(&self) -> u32 {
+        self.shown.last().copied().unwrap_or(1)
+    }
+
+    /// The slide presented after a slide, `None` after the last one.
+    pub fn next(&self, slide: u32) -> Option<u32> {
+        self.shown.iter().copied().find(|shown| *shown > slide)
+    }
+
+    /// The slide presented before a slide, `None` before the first one.
+    pub fn previous(&self, slide: u32) -> Option<u32> {
+        self.shown
+            .iter()
+            .copied()
+            .rev()
+            .find(|shown| *shown < slide)
+    }
+
+    /// The numbers of the slides presented, in the order of the deck.
+    pub fn slides(&self) -> &[u32] {
+        &self.shown
+    }
+
+    /// Check whether a slide is presented.
+    pub fn contains(&self, slide: u32) -> bool {
+        self.shown.contains(&slide)
+    }
+
+    /// The first slide presented of the next section of the deck that has one, `None` in the last of them.
+    pub fn next_section(&self, deck: &Deck, slide: u32) -> Option<u32> {
+        let start = deck.next_section(slide as usize - 1)?;
+        self.next(start as u32)
+    }
+
+    /// The first slide presented of the previous section of the deck that has one, `None` in the first of them.
+    pub fn previous_section(&self, deck: &Deck, slide: u32) -> Option<u32> {
+        let mut index = slide as usize - 1;
+        while let Some(start) = deck.previous_section(index) {
+            let section = &deck.slides[start].section;
+            if let Some(first) = self
+                .next(start as u32)
+                .filter(|first| &deck.slides[*first as usize - 1].section == section)
+            {
+                return Some(first);
+            }
+            index = start;
+        }
+        None
+    }
+
+    /// The position of a slide among the slides presented, starting at 1.
+    /// # Returns
+    /// The position, or `None` for a hidden slide.
+    pub fn position(&self, slide: u32) -> Option<u32> {
+        self.shown
+            .iter()
+            .position(|shown| *shown == slide)
+            .map(|position| position as u32 + 1)
+    }
+}
+
+/// Parse the ranges of slide numbers, e.g. `3-9,12`.
+pub fn parse_ranges(text: &str) -> Result<Vec<(u32, u32)>, String> {
+    let invalid = || format!("Invalid slides '{}', e.g. 3-9,12", text);
+    text.split(',')
+        .map(|range| {
+            let range = range.trim();
+            let (first, last) = range.split_once('-').unwrap_or((range, range));
+            let first: u32 = first.trim().parse().map_err(|_| invalid())?;
+            let last: u32 = last.trim().parse().map_err(|_| invalid())?;
+            if first == 0 || last < first {
+                return Err(invalid());
+            }
+            Ok((first, last))
+        })
+        .collect()
+}
+
+/// Split a comma separated list of tags.
+pub fn parse_tags(text: &str) -> Vec<String> {
+    text.split(',')
+        .map(|tag| tag.trim().to_string())
+        .filter(|tag| !tag.is_empty())
+        .collect()
+}
+
+/// The tags of a slide, a list or a single tag.
+fn tags(front_matter: &Settings) -> Vec<String> {
+    match manifest::list(front_matter, "tags") {
+        Some(tags) => tags.clone(),
+        None => manifest::scalar(front_matter, "tags")
+            .map(parse_tags)
+            .unwrap_or_default(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::deck::Slide;
+    use crate::manifest::Value;
+    use std::path::PathBuf;
+
+    /// A deck of slides in the given sections, with the given tags.
+    fn deck(slides: &[(&str, &str)]) -> Deck {
+        Deck {
+            dir: PathBuf::from("."),
+            settings: Settings::new(),
+            slides: slides
+                .iter()
+                .map(|(section, tags)| Slide {
+                    path: PathBuf::from("slide.md"),
+                    front_matter: Settings::from([(
+                        "tags".to_string(),
+                        Value::Scalar(tags.to_string()),
+                    )]),
+                    markdown: String::new(),
+                    section: Some(section.to_string()),
+                })
+                .collect(),
+        }
+    }
+
+    fn filter(only_tags: &str, skip_tags: &str, slides: &str) -> Filter {
+        Filter {
+            only_tags: parse_tags(only_tags),
+            skip_tags: parse_tags(skip_tags),
+            slides: if slides.is_empty() {
+                Vec::new()
+            } else {
+                parse_ranges(slides).unwrap()
+            },
+        }
+    }
+
+    #[test]
+    fn parses_ranges() {
+        assert_eq!(parse_ranges("3-9,12"), Ok(vec![(3, 9), (12, 12)]));
+        assert_eq!(parse_ranges(" 1 - 2 , 4 "), Ok(vec![(1, 2), (4, 4)]));
+    }
+
+    #[test]
+    fn rejects_invalid_ranges() {
+        for text in ["", "0", "9-3", "3-", "a", "1,,2"] {
+            assert_eq!(
+                parse_ranges(text),
+                Err(format!("Invalid slides '{}', e.g. 3-9,12", text))
+            );
+        }
+    }
+
+    #[test]
+    fn parses_tags() {
+        assert_eq!(parse_tags(" core, ,demo "), vec!["core", "demo"]);
+    }
+
+    #[test]
+    fn picks_slides_by_tag_and_number() {
+        let deck = deck(&[
+            ("a", "core"),
+            ("a", "demo"),
+            ("b", "core, extra"),
+            ("b", ""),
+        ]);
+        assert_eq!(
+            Subset::new(&deck, &filter("core", "", ""))
+                .unwrap()
+                .slides(),
+            &[1, 3]
+        );
+        assert_eq!(
+            Subset::new(&deck, &filter("", "extra", ""))
+                .unwrap()
+                .slides(),
+            &[1, 2, 4]
+        );
+        assert_eq!(
+            Subset::new(&deck, &filter("core", "extra", ""))
+                .unwrap()
+                .slides(),
+            &[1]
+        );
+        assert_eq!(
+            Subset::new(&deck, &filter("", "", "2-3")).unwrap().slides(),
+            &[2, 3]
+        );
+        assert!(Subset::new(&deck, &filter("missing", "", "")).is_err());
+    }
+
+    #[test]
+    fn steps_over_hidden_slides() {
+        let deck = deck(&[("a", ""), ("a", "extra"), ("a", ""), ("a", "extra")]);
+        let subset = Subset::new(&deck, &filter("", "extra", "")).unwrap();
+        assert_eq!(subset.next(1), Some(3));
+        assert_eq!(subset.next(3), None);
+        assert_eq!(subset.previous(3), Some(1));
+        assert_eq!(subset.previous(2), Some(1));
+        assert_eq!((subset.first(), subset.last(), subset.len()), (1, 3, 2));
+        assert_eq!(subset.position(3), Some(2));
+        assert_eq!(subset.position(2), None);
+    }
+
+    #[test]
+    fn skips_the_sections_without_a_slide_presented() {
+        let deck = deck(&[
+            ("a", ""),
+            ("a", ""),
+            ("b", "extra"),
+            ("b", "extra"),
+            ("c", ""),
+        ]);
+        let subset = Subset::new(&deck, &filter("", "extra", "")).unwrap();
+        assert_eq!(subset.next_section(&deck, 1), Some(5));
+        assert_eq!(subset.next_section(&deck, 5), None);
+        assert_eq!(subset.previous_section(&deck, 5), Some(1));
+        assert_eq!(subset.previous_section(&deck, 2), None);
+    }
+}
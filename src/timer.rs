@@ -0,0 +1,362 @@
+//! Timer keeps the time of a talk against its plan.
+//! The duration of the talk is set with `duration` in `deck.yml` or in the front matter of a single file deck,
+//! and a slide can take a time of its own with `time` in its front matter:
+//!
+//! ```yaml
+//! duration: 20m
+//! ```
+//!
+//! The time left after the slides with a time of their own is shared evenly by the other slides.
+//! The footer shows the time with the placeholders {clock}, {elapsed}, {remaining}, {slide_time} and {pace},
+//! and turns yellow when the slide runs over its time, and red when the talk is a slide behind or runs over.
+
+use crate::utils::{self, format_duration};
+
+use std::collections::HashMap;
+use std::io::{stdout, Write};
+use std::sync::mpsc;
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// How the talk keeps to its plan.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Pace {
+    /// The talk is on time, or has no plan.
+    OnTime,
+    /// The slide runs over its time.
+    Behind,
+    /// The talk is a whole slide behind, or runs over its duration.
+    Late,
+}
+
+impl Pace {
+    /// The name of the pace, as it is reported by the remote.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Pace::OnTime => "on-time",
+            Pace::Behind => "behind",
+            Pace::Late => "late",
+        }
+    }
+}
+
+/// The time of a talk.
+#[derive(Clone)]
+pub struct Timer {
+    started: Instant,
+    slide_started: Instant,
+    /// The index of the slide being shown.
+    slide: usize,
+    /// The duration of the talk.
+    duration: Option<Duration>,
+    /// The time the plan has each slide end at, from the start of the talk. Empty without a plan.
+    plan: Vec<Duration>,
+}
+
+impl Timer {
+    /// Start the timer of a talk.
+    pub fn new() -> Timer {
+        Timer {
+            started: Instant::now(),
+            slide_started: Instant::now(),
+            slide: 0,
+            duration: None,
+            plan: Vec::new(),
+        }
+    }
+
+    /// Plan the talk.
+    /// # Arguments
+    /// * `duration` - The duration of the talk.
+    /// * `times` - The time of each slide, `None` for a slide without a time of its own.
+    pub fn plan(&mut self, duration: Option<Duration>, times: &[Option<Duration>]) {
+        let planned: Duration = times.iter().flatten().sum();
+        let duration = duration.or(if planned.is_zero() {
+            None
+        } else {
+            Some(planned)
+        });
+        self.duration = duration;
+        self.plan.clear();
+        let duration = match duration {
+            Some(duration) => duration,
+            None => return,
+        };
+
+        // The slides without a time of their own share the rest of the talk.
+        let unplanned = times.iter().filter(|time| time.is_none()).count() as u32;
+        let share = if unplanned > 0 {
+            duration.saturating_sub(planned) / unplanned
+        } else {
+            Duration::ZERO
+        };
+        let mut end = Duration::ZERO;
+        for time in times {
+            end += time.unwrap_or(share);
+            self.plan.push(end);
+        }
+    }
+
+    /// Move the timer to a slide, the time of the slide starts again when it changes.
+    pub fn enter(&mut self, slide: usize) {
+        if slide != self.slide {
+            self.slide = slide;
+            self.slide_started = Instant::now();
+        }
+    }
+
+    /// Check whether the talk has a plan.
+    pub fn is_planned(&self) -> bool {
+        !self.plan.is_empty()
+    }
+
+    /// How the talk keeps to its plan.
+    pub fn pace(&self) -> Pace {
+        let elapsed = self.started.elapsed();
+        let end = match self.plan.get(self.slide) {
+            Some(end) => *end,
+            None => return Pace::OnTime,
+        };
+        let next = self.plan.get(self.slide + 1).copied().unwrap_or(end);
+        if self.duration.is_some_and(|duration| elapsed > duration) || elapsed > next {
+            Pace::Late
+        } else if elapsed > end {
+            Pace::Behind
+        } else {
+            Pace::OnTime
+        }
+    }
+
+    /// The time since the start of the talk.
+    pub fn elapsed(&self) -> Duration {
+        self.started.elapsed()
+    }
+
+    /// The time since the slide was entered.
+    pub fn slide_elapsed(&self) -> Duration {
+        self.slide_started.elapsed()
+    }
+
+    /// The seconds left of the talk, below zero once it runs over. `None` for a talk without a duration.
+    pub fn remaining(&self) -> Option<i64> {
+        self.duration
+            .map(|duration| duration.as_secs() as i64 - self.elapsed().as_secs() as i64)
+    }
+
+    /// Fill in the time placeholders of the footer, {clock}, {elapsed}, {remaining}, {slide_time} and {pace}.
+    pub fn fill(&self, text: &str) -> String {
+        let now = utils::local_time();
+        let elapsed = self.elapsed();
+        let remaining = self.remaining().map(signed).unwrap_or_default();
+        // The pace is the time the talk is ahead of the plan, or behind it with a minus.
+        let pace = match self.plan.get(self.slide) {
+            Some(end) if elapsed > *end => signed(end.as_secs() as i64 - elapsed.as_secs() as i64),
+            Some(end) => format!(
+                "+{}",
+                signed(end.as_secs() as i64 - elapsed.as_secs() as i64)
+            ),
+            None => String::new(),
+        };
+        text.replace("{clock}", &format!("{:02}:{:02}", now.tm_hour, now.tm_min))
+            .replace("{elapsed}", &format_duration(elapsed))
+            .replace("{remaining}", &remaining)
+            .replace("{slide_time}", &format_duration(self.slide_elapsed()))
+            .replace("{pace}", &pace)
+    }
+
+    /// The color of the footer, by the pace of the talk.
+    /// The colors are the `timer_behind` and the `timer_late` keys of the style config.
+    pub fn color<'a>(&self, style_map: &'a HashMap<String, String>) -> Option<&'a str> {
+        let (key, default) = match self.pace() {
+            Pace::OnTime => return None,
+            Pace::Behind => ("timer_behind", "yellow"),
+            Pace::Late => ("timer_late", "red"),
+        };
+        Some(
+            style_map
+                .get(key)
+                .map(|color| color.as_str())
+                .filter(|color| !color.is_empty())
+                .unwrap_or(default),
+        )
+    }
+}
+
+/// Check whether a footer shows the time, and so has to be drawn again as the time goes.
+pub fn shows_time(text: &str) -> bool {
+    [
+        "{clock}",
+        "{elapsed}",
+        "{remaining}",
+        "{slide_time}",
+        "{pace}",
+    ]
+    .iter()
+    .any(|placeholder| text.contains(placeholder))
+}
+
+/// Parse a duration, e.g. `20m`, `90s`, `1h30m`, `2:30` for minutes and seconds, or `20` for minutes.
+pub fn parse_duration(text: &str) -> Option<Duration> {
+    let text = text.trim().trim_matches(|c| c == '"' || c == '\'');
+    if let Ok(minutes) = text.parse::<u64>() {
+        return Some(Duration::from_secs(minutes * 60));
+    }
+    if text.contains(':') {
+        let seconds = text.split(':').try_fold(0, |total, part| {
+            part.parse::<u64>().ok().map(|n| total * 60 + n)
+        })?;
+        return Some(Duration::from_secs(seconds));
+    }
+
+    let mut seconds = 0;
+    let mut number = String::new();
+    for c in text.chars() {
+        match c {
+            '0'..='9' => number.push(c),
+            'h' | 'm' | 's' if !number.is_empty() => {
+                let unit = match c {
+                    'h' => 3600,
+                    'm' => 60,
+                    _ => 1,
+                };
+                seconds += number.parse::<u64>().ok()? * unit;
+                number.clear();
+            }
+            ' ' => continue,
+            _ => return None,
+        }
+    }
+    if !number.is_empty() || text.is_empty() {
+        return None;
+    }
+    Some(Duration::from_secs(seconds))
+}
+
+/// Format seconds that can be negative, e.g. `-01:30`.
+fn signed(seconds: i64) -> String {
+    let text = format_duration(Duration::from_secs(seconds.unsigned_abs()));
+    if seconds < 0 {
+        format!("-{}", text)
+    } else {
+        text
+    }
+}
+
+/// Draws the footer again every second, while the presentation waits for a key.
+/// The footer stops being drawn when the ticker is dropped.
+pub struct Ticker {
+    stop: Option<mpsc::Sender<()>>,
+    handle: Option<thread::JoinHandle<()>>,
+}
+
+impl Ticker {
+    /// Start drawing the footer every second.
+    /// # Arguments
+    /// * `draw` - Draws the footer, from the time of the timer.
+    pub fn start(draw: impl Fn() -> String + Send + 'static) -> Ticker {
+        let (stop, stopped) = mpsc::channel::<()>();
+        let handle = thread::spawn(move || {
+            while let Err(mpsc::RecvTimeoutError::Timeout) =
+                stopped.recv_timeout(Duration::from_secs(1))
+            {
+                // The cursor is put back, so that the footer does not move the input of a prompt.
+                let mut stdout = stdout().lock();
+                let _ = write!(
+                    stdout,
+                    "{}{}{}",
+                    termion::cursor::Save,
+                    draw(),
+                    termion::cursor::Restore
+                );
+                let _ = stdout.flush();
+            }
+        });
+        Ticker {
+            stop: Some(stop),
+            handle: Some(handle),
+        }
+    }
+}
+
+impl Drop for Ticker {
+    fn drop(&mut self) {
+        // Dropping the sender wakes the thread up, and it returns.
+        self.stop.take();
+        if let Some(handle) = self.handle.take() {
+            let _ = handle.join();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn minutes(n: u64) -> Duration {
+        Duration::from_secs(n * 60)
+    }
+
+    #[test]
+    fn parses_durations() {
+        assert_eq!(parse_duration("20m"), Some(minutes(20)));
+        assert_eq!(parse_duration("90s"), Some(Duration::from_secs(90)));
+        assert_eq!(parse_duration("1h 30m"), Some(minutes(90)));
+        assert_eq!(parse_duration("2:30"), Some(Duration::from_secs(150)));
+        assert_eq!(parse_duration("1:00:00"), Some(minutes(60)));
+        assert_eq!(parse_duration("\"20\""), Some(minutes(20)));
+    }
+
+    #[test]
+    fn rejects_text_that_is_not_a_duration() {
+        assert_eq!(parse_duration(""), None);
+        assert_eq!(parse_duration("m"), None);
+        assert_eq!(parse_duration("20x"), None);
+        assert_eq!(parse_duration("1h30"), None);
+        assert_eq!(parse_duration("2:3a"), None);
+    }
+
+    #[test]
+    fn shares_the_rest_of_the_talk_among_the_other_slides() {
+        let mut timer = Timer::new();
+        timer.plan(Some(minutes(20)), &[Some(minutes(2)), None, None]);
+        assert_eq!(timer.plan, vec![minutes(2), minutes(11), minutes(20)]);
+        assert!(timer.is_planned());
+    }
+
+    #[test]
+    fn adds_up_the_times_of_a_talk_without_a_duration() {
+        let mut timer = Timer::new();
+        timer.plan(None, &[Some(minutes(1)), Some(minutes(4))]);
+        assert_eq!(timer.remaining(), Some(300));
+        assert_eq!(timer.plan, vec![minutes(1), minutes(5)]);
+    }
+
+    #[test]
+    fn has_no_plan_without_any_time() {
+        let mut timer = Timer::new();
+        timer.plan(None, &[None, None]);
+        assert!(!timer.is_planned());
+        assert_eq!(timer.remaining(), None);
+        assert_eq!(timer.pace(), Pace::OnTime);
+    }
+
+    #[test]
+    fn keeps_to_the_plan_at_the_start() {
+        let mut timer = Timer::new();
+        timer.plan(Some(minutes(10)), &[None, None]);
+        assert_eq!(timer.pace(), Pace::OnTime);
+        assert_eq!(timer.fill("{pace}"), "+05:00");
+    }
+
+    #[test]
+    fn formats_signed_times() {
+        assert_eq!(signed(90), "01:30");
+        assert_eq!(signed(-90), "-01:30");
+    }
+
+    #[test]
+    fn finds_the_time_placeholders() {
+        assert!(shows_time("{title}  {elapsed}"));
+        assert!(!shows_time("{title}  {slide}/{total}"));
+    }
+}
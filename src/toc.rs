@@ -0,0 +1,142 @@
+//! Toc generates the table of contents of a deck.
+//! A `<!-- toc -->` line in a slide, or `toc: true` in the front matter of a slide,
+//! expands into a list of the titles of the slides, grouped by section, each linking to its slide.
+//! The title of a slide is its first heading, slides without a heading are left out.
+
+use crate::include::{is_closing_fence, open_fence};
+use crate::links;
+
+use std::collections::HashMap;
+
+use markdown::mdast;
+
+/// The marker the table of contents is generated in place of.
+pub const MARKER: &str = "<!-- toc -->";
+
+/// A slide as it is listed in the table of contents.
+pub struct Entry {
+    /// The first heading of the slide.
+    pub title: Option<String>,
+    /// The section the slide belongs to.
+    pub section: Option<String>,
+}
+
+/// Check whether a slide has the marker of the table of contents, outside of its code fences.
+pub fn has_marker(markdown: &str) -> bool {
+    let mut fence: Option<String> = None;
+    for line in markdown.lines() {
+        if let Some(open) = &fence {
+            if is_closing_fence(line, open) {
+                fence = None;
+            }
+        } else if let Some((open, _)) = open_fence(line) {
+            fence = Some(open);
+        } else if line.trim() == MARKER {
+            return true;
+        }
+    }
+    false
+}
+
+/// The title of a slide, the text of its first heading.
+pub fn title(markdown: &str) -> Option<String> {
+    let tree = markdown::to_mdast(markdown, &markdown::ParseOptions::default()).ok()?;
+    let heading = first_heading(&tree)?;
+    let title = links::strip_alignment(&plain_text(heading));
+    let title = title.trim();
+    if title.is_empty() {
+        return None;
+    }
+    Some(title.to_string())
+}
+
+/// Generate the table of contents of a deck as a markdown list.
+/// # Arguments
+/// * `entries` - The slides of the deck.
+/// * `anchors` - The anchors of the deck, the titles link to them.
+/// * `skip` - The index of the slide holding the table of contents, which is not listed.
+pub fn generate(entries: &[Entry], anchors: &HashMap<String, usize>, skip: usize) -> String {
+    let mut lines = Vec::new();
+    let mut section: Option<&str> = None;
+
+    for (index, entry) in entries.iter().enumerate() {
+        let title = match &entry.title {
+            Some(title) if index != skip => title,
+            _ => continue,
+        };
+
+        // A section is listed once, with its slides nested under it.
+        if entry.section.as_deref() != section {
+            section = entry.section.as_deref();
+            if let Some(section) = section {
+                lines.push(format!("- **{}**", section));
+            }
+        }
+        let indent = if section.is_some() { "  " } else { "" };
+
+        match anchor(title, index, anchors) {
+            Some(anchor) => lines.push(format!("{}- [{}](#{})", indent, title, anchor)),
+            None => lines.push(format!("{}- {}", indent, title)),
+        }
+    }
+
+    lines.join("\n")
+}
+
+/// Expand the table of contents of a slide.
+/// The marker is replaced with the table, a slide with `toc: true` and without a marker has it appended.
+pub fn expand(markdown: &str, table: &str) -> String {
+    if !has_marker(markdown) {
+        return format!("{}\n\n{}\n", markdown.trim_end(), table);
+    }
+
+    let mut result = Vec::new();
+    let mut fence: Option<String> = None;
+    for line in markdown.lines() {
+        if let Some(open) = &fence {
+            if is_closing_fence(line, open) {
+                fence = None;
+            }
+        } else if let Some((open, _)) = open_fence(line) {
+            fence = Some(open);
+        } else if line.trim() == MARKER {
+            result.push(table.to_string());
+            continue;
+        }
+        result.push(line.to_string());
+    }
+
+    result.join("\n")
+}
+
+/// The anchor of the title of a slide.
+/// A heading repeated in the deck has a numbered anchor, the one that leads to the slide is picked.
+fn anchor(title: &str, index: usize, anchors: &HashMap<String, usize>) -> Option<String> {
+    let slug = links::slug(title);
+    (0..=anchors.len())
+        .map(|count| match count {
+            0 => slug.clone(),
+            count => format!("{}-{}", slug, count),
+        })
+        .find(|anchor| anchors.get(anchor) == Some(&index))
+}
+
+/// The first heading of a markdown tree.
+fn first_heading(node: &mdast::Node) -> Option<&mdast::Node> {
+    if let mdast::Node::Heading(_) = node {
+        return Some(node);
+    }
+    node.children()?.iter().find_map(first_heading)
+}
+
+/// The text of a node, without its formatting.
+fn plain_text(node: &mdast::Node) -> String {
+    match node {
+        mdast::Node::Text(text) => text.value.clone(),
+        mdast::Node::InlineCode(code) => code.value.clone(),
+        node => node
+            .children()
+            .map(|children| children.iter().map(plain_text).collect())
+            .unwrap_or_default(),
+    }
+}
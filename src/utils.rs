@@ -113,6 +113,40 @@ pub fn remove_comments(text: &str) -> String {
     result.to_string()
 }
 
+/// The speaker notes of a slide, the text of its comments.
+/// The `<!-- toc -->` marker is not a note.
+pub fn speaker_notes(text: &str) -> Vec<String> {
+    let re = Regex::new(r"(?s)<!--(.*?)-->").unwrap();
+    re.captures_iter(text)
+        .map(|captures| captures[1].trim().to_string())
+        .filter(|note| !note.is_empty() && note != "toc")
+        .collect()
+}
+
+/// Parse the contents of a style.yml file into a style map.
+/// Every `key: value` line is an entry of the map, nested keys such as the runtimes are flattened.
+/// The key bindings of the `keys` section are not styles, and are left out.
+pub fn parse_style(style_content: &str) -> HashMap<String, String> {
+    let mut in_keys = false;
+    style_content
+        .lines()
+        .filter(|line| {
+            let nested = line.starts_with(' ') || line.starts_with('\t');
+            if !nested && !line.trim().is_empty() && !line.trim_start().starts_with('#') {
+                in_keys = line.trim_end() == "keys:";
+            }
+            !in_keys
+        })
+        .filter_map(|line| {
+            let mut parts = line.splitn(2, ':');
+            Some((
+                parts.next()?.trim().to_string(),
+                parts.next()?.trim().to_string(),
+            ))
+        })
+        .collect()
+}
+
 pub fn create_style(project: std::path::PathBuf) -> Result<(), Box<dyn std::error::Error>> {
     let mut log = Logger::new();
     let style_path = project.join("style.yml");
@@ -163,9 +197,39 @@ pub fn create_style(project: std::path::PathBuf) -> Result<(), Box<dyn std::erro
         #themes:[base16-ocean.dark,base16-eighties.dark,base16-mocha.dark,base16-ocean.light, Solarized (dark) and Solarized (light)]
         syntax_bg: false
         
-        # shows the progress of the presentation: i [1/10]  : current slide/total slides in the bottom left corner
+        # code_line_numbers shows a line number gutter next to every code block
+        # a single code block can show it with the `numbers` flag: ```rust {numbers}
+        code_line_numbers: false
+        # code_caption styles the caption of a code block set with the `title` attribute: ```rust {title=main.rs}
+        code_caption: black on white
+        # diff_added and diff_removed are the background colors of the + and - lines of ```diff blocks, or of blocks with the `diff` flag
+        diff_added: green
+        diff_removed: red
+        
+        # shows the progress of the presentation: [1/10] : current slide/total slides in the bottom left corner
         progress: false
         
+        # footer is shown on the last line of the terminal, with the placeholders {slide}, {total}, {section}, {title}, {clock}, {elapsed},
+        # {remaining}, {slide_time} and {pace} (see Timing a Talk in the README)
+        # when no footer is set, progress: true shows [{slide}/{total}] {section}
+        footer:
+        footer_color: white
+        # progress_bar fills the rest of the footer with a bar, up to the current slide
+        progress_bar: false
+        # timer_behind and timer_late color the footer when a talk with a duration falls behind its plan
+        # behind is a slide running over its time, late is a whole slide behind or past the end of the talk
+        timer_behind: yellow
+        timer_late: red
+        
+        # mouse lets clicks on the left and right half of the screen move between slides, and the wheel scroll
+        # clicking a code block runs it, and clicking a link follows it. Set it to false to select text with the mouse
+        mouse: true
+        
+        # pause_message is shown on the pause screen, with {minutes} for the minutes of the countdown
+        # pause_minutes are counted down under the message, unless the pause command is given its own minutes
+        pause_message: Back in {minutes} minutes
+        pause_minutes: 5
+        
         # runtime map is used to store the runtimes for different languages
         # you can add your own runtimes for different languages. Currently, the following runtimes are supported:
         
@@ -197,6 +261,45 @@ pub fn create_style(project: std::path::PathBuf) -> Result<(), Box<dyn std::erro
     }
 }
 
+/// The local date and time, in the time zone of the system.
+pub fn local_time() -> libc::tm {
+    let now = unsafe { libc::time(std::ptr::null_mut()) };
+    let mut time: libc::tm = unsafe { std::mem::zeroed() };
+    unsafe { libc::localtime_r(&now, &mut time) };
+    time
+}
+
+/// Format a duration as minutes and seconds, or hours, minutes and seconds.
+pub fn format_duration(duration: std::time::Duration) -> String {
+    let seconds = duration.as_secs();
+    if seconds >= 3600 {
+        format!(
+            "{}:{:02}:{:02}",
+            seconds / 3600,
+            seconds / 60 % 60,
+            seconds % 60
+        )
+    } else {
+        format!("{:02}:{:02}", seconds / 60, seconds % 60)
+    }
+}
+
+/// Escape a string for JSON.
+pub fn escape_json(text: &str) -> String {
+    let mut escaped = String::new();
+    for c in text.chars() {
+        match c {
+            '"' => escaped.push_str("\\\""),
+            '\\' => escaped.push_str("\\\\"),
+            '\n' => escaped.push_str("\\n"),
+            '\t' => escaped.push_str("\\t"),
+            c if (c as u32) < 0x20 => escaped.push_str(&format!("\\u{:04x}", c as u32)),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
 pub fn check_if_text_is_right_aligned(prettified: &str) -> bool {
     let line_re = regex::Regex::new(r"\$\[r\]\$").unwrap();
     let block_re = regex::Regex::new(r"\$\[r\]").unwrap();
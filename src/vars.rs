@@ -0,0 +1,81 @@
+//! Vars substitutes the `{{ name }}` variables of a deck in the slides and in `style.yml`.
+//! Variables are the scalar settings of `deck.yml` and of the front matter of a slide,
+//! along with the built-ins `slide`, `total` and `today`.
+
+use crate::include::{is_closing_fence, open_fence};
+use crate::manifest::{Settings, Value};
+use crate::utils;
+
+use std::collections::HashMap;
+
+use lazy_static::lazy_static;
+use regex::{Captures, Regex};
+
+lazy_static! {
+    /// This is used to match a variable, e.g. {{ event }}
+    static ref VARIABLE_RE: Regex = Regex::new(r"\{\{\s*([A-Za-z_][A-Za-z0-9_-]*)\s*\}\}").unwrap();
+}
+
+/// Add the scalar settings to the variables, replacing the variables with the same name.
+pub fn extend(variables: &mut HashMap<String, String>, settings: &Settings) {
+    for (key, value) in settings {
+        if let Value::Scalar(value) = value {
+            variables.insert(key.clone(), value.clone());
+        }
+    }
+}
+
+/// The built-in variables of a slide.
+/// # Arguments
+/// * `slide` - The number of the slide, among the slides presented.
+/// * `total` - The number of slides presented.
+pub fn builtins(slide: u32, total: u32) -> HashMap<String, String> {
+    let mut variables = HashMap::new();
+    variables.insert("slide".to_string(), slide.to_string());
+    variables.insert("total".to_string(), total.to_string());
+    variables.insert("today".to_string(), today());
+    variables
+}
+
+/// Substitute the variables in a text.
+/// Code fences are left alone, and unknown variables are kept as they are written.
+pub fn substitute(text: &str, variables: &HashMap<String, String>) -> String {
+    let mut result = Vec::new();
+    let mut fence: Option<String> = None;
+
+    for line in text.lines() {
+        if let Some(open) = &fence {
+            if is_closing_fence(line, open) {
+                fence = None;
+            }
+            result.push(line.to_string());
+            continue;
+        }
+        if let Some((open, _)) = open_fence(line) {
+            fence = Some(open);
+            result.push(line.to_string());
+            continue;
+        }
+
+        let substituted = VARIABLE_RE.replace_all(line, |captures: &Captures| {
+            variables
+                .get(&captures[1])
+                .cloned()
+                .unwrap_or(captures[0].to_string())
+        });
+        result.push(substituted.to_string());
+    }
+
+    result.join("\n")
+}
+
+/// Today's date as YYYY-MM-DD, in the local time zone.
+fn today() -> String {
+    let now = utils::local_time();
+    format!(
+        "{:04}-{:02}-{:02}",
+        now.tm_year + 1900,
+        now.tm_mon + 1,
+        now.tm_mday
+    )
+}